@@ -43,8 +43,6 @@ mod tests {
 
     #[test]
     fn row_bbs() {
-        let board = Board { tiles: [0, 0], turn : 0};
-
         for i in 0..N {
             let mut bb = WINNING_STATES[i];
 
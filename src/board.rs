@@ -26,7 +26,7 @@ impl Board {
     pub fn make(&mut self, index: usize) {
         let sqr = 1 << (index - 1);
 
-        assert!(index >= 1 && index <= N2);
+        assert!((1..=N2).contains(&index));
         assert_ne!(sqr & self.empty(), 0);
 
         self.tiles[self.turn] ^= sqr;
@@ -75,11 +75,48 @@ impl Board {
             }
         }
 
-        assert!(moves.len() > 0);
+        assert!(!moves.is_empty());
 
         moves
     }
 
+    // Reads the squares as Board::print writes them, X, O or . row by row,
+    // ignoring anything else. The side to move is the one with fewer
+    // tiles, X when they have as many.
+    pub fn parse(squares: &str) -> Option<Board> {
+        let mut board = Board { tiles: [0, 0], turn: PLAYER1 };
+        let mut sqr = 0;
+
+        for c in squares.chars() {
+            let player = match c.to_ascii_uppercase() {
+                'X' => Some(PLAYER1),
+                'O' => Some(PLAYER2),
+                '.' => None,
+                _ => continue
+            };
+
+            if sqr == N2 {
+                return None;
+            }
+
+            if let Some(player) = player {
+                board.tiles[player] |= 1 << sqr;
+            }
+
+            sqr += 1;
+        }
+
+        if sqr != N2 {
+            return None;
+        }
+
+        if board.tiles[PLAYER1].count_ones() > board.tiles[PLAYER2].count_ones() {
+            board.turn = PLAYER2;
+        }
+
+        Some(board)
+    }
+
     pub fn print(&self) {
         println!();
         
@@ -135,9 +172,29 @@ impl Board {
         0
     }
 
+    // The score weighed by how early the game ended, so that winning with
+    // fewer tiles on the board is worth more and losing is worth less.
+    // Being counted from the tiles, it doesn't depend on where the search
+    // started.
+    pub fn distance_score(&self) -> isize {
+        let tiles = (self.tiles[PLAYER1] | self.tiles[PLAYER2]).count_ones() as isize;
+        self.score() * (MAX_SCORE - tiles)
+    }
+
+    // The moves left until the game is won or lost with a distance score
+    // found from this board, or None if it's a draw.
+    pub fn moves_to_end(&self, score: isize) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+
+        let tiles = (self.tiles[PLAYER1] | self.tiles[PLAYER2]).count_ones() as isize;
+        Some((MAX_SCORE - score.abs() - tiles) as usize)
+    }
+
     pub fn random_move(&self) -> usize {
         let moves = self.gen_moves();
-        assert!(moves.len() > 0);
+        assert!(!moves.is_empty());
 
         let pick: usize = rand::thread_rng().gen_range(0, moves.len()) as usize;
         moves[pick]
@@ -172,6 +229,15 @@ impl Board {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse() {
+        assert!(Board::parse("xo. .x. ..o") == Some(Board { tiles: [0b000010001, 0b100000010], turn: 0 }));
+        assert!(Board::parse("XX. O.. ...") == Some(Board { tiles: [0b000000011, 0b000001000], turn: 1 }));
+
+        assert!(Board::parse("XO.").is_none());
+        assert!(Board::parse("XO.XO.XO.X").is_none());
+    }
+
     #[test]
     fn test_find_forced() {
         let tests = [
@@ -187,11 +253,11 @@ mod tests {
 
             // Every test is performed for both turns to make sure it can 
             // block winning moves and make winning moves.
-            let mut clone = board.clone();
+            let mut clone = board;
             clone.turn ^= 1;
             
             assert_eq!(board.find_forced(), test.1);
-            assert_eq!(board.find_forced(), test.1);
+            assert_eq!(clone.find_forced(), test.1);
         }
     }
 }
\ No newline at end of file
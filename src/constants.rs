@@ -1,3 +1,7 @@
 pub const N: usize = 3;
 pub const N2: usize = N * N;
 pub const N_STATES: usize = 2*N + 2;
+
+// Bounds the scores of the search, which are weighed by how soon the game
+// ends (see Board::distance_score).
+pub const MAX_SCORE: isize = N2 as isize + 1;
@@ -0,0 +1,223 @@
+extern crate rand;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::constants::*;
+use crate::board::{Board, State};
+use crate::players::player::Player;
+
+// What the agent is told besides the observation and the reward.
+pub struct Info {
+    // The squares the agent can play next.
+    pub mask: [bool; N2],
+
+    // The square the opponent answered with, indexed from 0.
+    pub opponent_action: Option<usize>,
+
+    // The agent played an occupied square or one off the board, which loses
+    // the game.
+    pub illegal: bool
+}
+
+// A game against a fixed opponent behind a Gym-like interface, so that
+// agents don't have to deal with the board. Actions are squares indexed
+// from 0, like the observation and the mask. The observation is the three
+// planes of Board::to_binary from the agent's side, and the reward is 1
+// for a win, -1 for a loss and 0 otherwise.
+//
+// The seed decides who starts each game. Opponents draw their own
+// random numbers, so a random opponent isn't reproducible.
+pub struct Env {
+    pub board: Board,
+    pub opponent: Box<dyn Player>,
+
+    // Who starts, or at random if None.
+    pub agent_starts: Option<bool>,
+
+    agent: usize,
+    done: bool,
+    rng: StdRng
+}
+
+impl Env {
+    pub fn new(opponent: Box<dyn Player>, seed: u64) -> Env {
+        Env {
+            board: Board { tiles: [0, 0], turn: 0 },
+            opponent,
+            agent_starts: None,
+            agent: 0,
+            done: true,
+            rng: StdRng::seed_from_u64(seed)
+        }
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    // Starts a new game, where the opponent may have already moved.
+    pub fn reset(&mut self) -> Vec<f64> {
+        let agent_starts = match self.agent_starts {
+            Some(agent_starts) => agent_starts,
+            None => self.rng.gen::<f64>() < 0.5
+        };
+
+        self.board = Board { tiles: [0, 0], turn: self.rng.gen_range(0, 2) };
+        self.agent = if agent_starts { self.board.turn } else { self.board.turn ^ 1 };
+        self.done = false;
+
+        if !agent_starts {
+            let move_ = self.opponent.best_move(&self.board);
+            self.board.make(move_);
+        }
+
+        self.observation()
+    }
+
+    pub fn step(&mut self, action: usize) -> (Vec<f64>, f64, bool, Info) {
+        assert!(!self.done, "The game is over, it needs a reset.");
+
+        let mut info = Info { mask: [false; N2], opponent_action: None, illegal: false };
+
+        // Squares off the board forfeit the game like occupied ones.
+        if action >= N2 || self.board.empty() & (1 << action) == 0 {
+            info.illegal = true;
+            self.done = true;
+
+            self.opponent.game_over(&self.board);
+
+            return (self.observation(), -1.0, true, info);
+        }
+
+        self.board.make(action + 1);
+
+        if self.board.state() == State::Unfinished {
+            let move_ = self.opponent.best_move(&self.board);
+            self.board.make(move_);
+
+            info.opponent_action = Some(move_ - 1);
+        }
+
+        let reward = self.reward();
+        self.done = self.board.state() != State::Unfinished;
+
+        if self.done {
+            self.opponent.game_over(&self.board);
+        }
+
+        info.mask = self.mask();
+
+        (self.observation(), reward, self.done, info)
+    }
+
+    // Empty squares while the game goes on.
+    pub fn mask(&self) -> [bool; N2] {
+        let mut mask: [bool; N2] = [false; N2];
+
+        if self.done || self.board.state() != State::Unfinished {
+            return mask;
+        }
+
+        for (i, legal) in mask.iter_mut().enumerate() {
+            *legal = self.board.empty() & (1 << i) != 0;
+        }
+
+        mask
+    }
+
+    fn observation(&self) -> Vec<f64> {
+        Board { tiles: self.board.tiles, turn: self.agent }.to_binary().to_vec()
+    }
+
+    fn reward(&self) -> f64 {
+        match self.board.state() {
+            State::Unfinished | State::Draw => 0.0,
+            _ => Board { tiles: self.board.tiles, turn: self.agent }.score() as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::Cell;
+    use crate::players::alphabeta::AlphaBeta;
+
+    // Plays the first empty square.
+    struct FirstEmpty;
+
+    impl Player for FirstEmpty {
+        fn best_move(&self, board: &Board) -> usize {
+            board.empty().trailing_zeros() as usize + 1
+        }
+    }
+
+    #[test]
+    fn test_episode() {
+        let mut env = Env::new(Box::new(FirstEmpty), 0);
+        env.agent_starts = Some(true);
+
+        let observation = env.reset();
+        assert_eq!(observation[N2 * 2..].iter().sum::<f64>(), N2 as f64);
+        assert!(env.mask().iter().all(|&legal| legal));
+
+        // The opponent fills the top row while the agent takes the centre
+        // column, and wins on 8.
+        let (_, reward, done, info) = env.step(4);
+        assert_eq!((reward, done, info.opponent_action), (0.0, false, Some(0)));
+
+        let (observation, _, _, info) = env.step(1);
+        assert_eq!(observation[1], 1.0);
+        assert_eq!(observation[N2], 1.0);
+        assert!(!info.mask[2]);
+
+        let (_, reward, done, info) = env.step(7);
+        assert_eq!((reward, done, info.opponent_action), (1.0, true, None));
+        assert!(info.mask.iter().all(|&legal| !legal));
+    }
+
+    // Records that it was told the game ended.
+    struct Watcher(Rc<Cell<bool>>);
+
+    impl Player for Watcher {
+        fn best_move(&self, board: &Board) -> usize {
+            FirstEmpty.best_move(board)
+        }
+
+        fn game_over(&self, _board: &Board) {
+            self.0.set(true);
+        }
+    }
+
+    #[test]
+    fn test_illegal_and_seeding() {
+        let mut env = Env::new(Box::new(AlphaBeta{}), 7);
+        env.agent_starts = Some(false);
+        env.reset();
+
+        let occupied = env.board.tiles[env.board.turn ^ 1].trailing_zeros() as usize;
+        let (_, reward, done, info) = env.step(occupied);
+        assert_eq!((reward, done, info.illegal), (-1.0, true, true));
+
+        env.reset();
+        let (_, reward, done, info) = env.step(N2);
+        assert_eq!((reward, done, info.illegal), (-1.0, true, true));
+
+        // The opponent learns about the forfeit as about any other end.
+        let over = Rc::new(Cell::new(false));
+        let mut env = Env::new(Box::new(Watcher(over.clone())), 0);
+        env.agent_starts = Some(false);
+        env.reset();
+
+        env.step(0);
+        assert!(over.get());
+
+        // The same seed gives the same starting sides.
+        let starts = |seed| {
+            let mut env = Env::new(Box::new(FirstEmpty), seed);
+            (0..10).map(|_| { env.reset(); env.board.tiles[0] | env.board.tiles[1] }).collect::<Vec<u64>>()
+        };
+
+        assert_eq!(starts(3), starts(3));
+    }
+}
@@ -0,0 +1,139 @@
+extern crate rand;
+use rand::Rng;
+
+use crate::constants::*;
+use crate::board::*;
+use crate::players::player::Player;
+
+// Games played against each opponent at every point of a learning curve.
+pub const EVAL_GAMES: usize = 20;
+
+// A point of a learning curve: the wins, losses and draws against each
+// opponent after a number of training games.
+pub struct Progress {
+    pub games: usize,
+    pub results: Vec<(&'static str, (usize, usize, usize))>
+}
+
+impl Progress {
+    // Plays EVAL_GAMES against every opponent and prints the results.
+    pub fn measure<T>(player: &T, games: usize, opponents: &[(&'static str, &dyn Player)]) -> Progress
+        where T: Player + ?Sized {
+
+        let results: Vec<(&'static str, (usize, usize, usize))> = opponents.iter()
+            .map(|&(name, opponent)| (name, play_match(player, opponent, EVAL_GAMES)))
+            .collect();
+
+        let scores: Vec<String> = results.iter()
+            .map(|(name, (wins, losses, draws))| format!("{} - {} - {} against {}", wins, losses, draws, name))
+            .collect();
+
+        println!("{} games: {}", games, scores.join(", "));
+
+        Progress { games, results }
+    }
+}
+
+// Returns the wins of each player and the number of draws.
+pub fn play_match<T, K>(player1: &T, player2: &K, n: usize) -> (usize, usize, usize)
+    where T: Player + ?Sized, K: Player + ?Sized {
+
+    let mut player1_wins = 0;
+    let mut player2_wins = 0;
+
+    for _ in 0..n {
+        match play_game(player1, player2) {
+            1  => player1_wins += 1,
+            -1 => player2_wins += 1,
+            _  => ()
+        }
+    }
+
+    let draws = n - player1_wins - player2_wins;
+    (player1_wins, player2_wins, draws)
+}
+
+pub fn play_game<T, K>(player1: &T, player2: &K) -> isize where T: Player + ?Sized, K: Player + ?Sized {
+    let mut board = Board { tiles: [0, 0], turn: rand::thread_rng().gen_range(0, 2) };
+
+    while board.state() == State::Unfinished {
+        let move_ = if board.turn == PLAYER1 {
+            player1.best_move(&board)
+        } else {
+            player2.best_move(&board)
+        };
+
+        board.make(move_);
+    }
+
+    player1.game_over(&board);
+    player2.game_over(&board);
+
+    match board.state() {
+        State::Player1Won => 1,
+        State::Player2Won => -1,
+        State::Draw => 0,
+        _ => panic!("Unfinished state after the game ended.")
+    }
+}
+
+// The empty square with the highest score, as a move. Scores of the taken
+// squares are ignored, so raw network outputs can be passed as they are.
+pub fn best_legal_move(board: &Board, scores: &[f64]) -> usize {
+    let empty = board.empty();
+
+    let mut best_move: usize = 0;
+    let mut highest_score = f64::MIN;
+
+    for (i, &score) in scores.iter().enumerate().take(N2) {
+        if empty & (1 << i) != 0 && score > highest_score {
+            highest_score = score;
+            best_move = i + 1;
+        }
+    }
+
+    assert!((1..=N2).contains(&best_move));
+
+    best_move
+}
+
+// Draws a move with the chances of a policy over the squares, which has
+// to be 0 for the taken ones.
+pub fn sample_move(policy: &[f64]) -> usize {
+    let mut pick: f64 = rand::thread_rng().gen();
+    let mut last = 0;
+
+    for (i, &p) in policy.iter().enumerate().take(N2) {
+        if p > 0.0 {
+            last = i + 1;
+
+            if pick < p {
+                return i + 1;
+            }
+
+            pick -= p;
+        }
+    }
+
+    // Rounding can leave a little probability over.
+    assert!(last != 0, "The policy has no legal moves.");
+
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_move() {
+        let policy = [0.0, 0.5, 0.0, 0.0, 0.25, 0.0, 0.0, 0.0, 0.25];
+
+        for _ in 0..100 {
+            assert!([2, 5, 9].contains(&sample_move(&policy)));
+        }
+
+        // Whatever rounding leaves over goes to the last legal square.
+        assert_eq!(sample_move(&[0.0, 0.0, 1e-18, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]), 3);
+    }
+}
@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate lazy_static;
+
+pub mod board;
+pub mod players;
+pub mod constants;
+pub mod bitboards;
+pub mod symmetries;
+pub mod threats;
+pub mod game;
+pub mod env;
@@ -1,23 +1,48 @@
-extern crate rand;
-use rand::Rng;
+use std::env;
 
-#[macro_use]
-extern crate lazy_static;
+use tictactoe::{board, game, threats};
+use tictactoe::constants::N2;
+use tictactoe::players::*;
+use tictactoe::players::networks::supervised::ALL_BOARDS;
 
-mod board;
-mod players;
-mod constants;
-mod bitboards;
-
-use board::*;
-use players::*;
+const USAGE: &str = "Usage: cargo run -- [train | match <games> | dataset <path> | benchmark | compare | prove | tablebase <path> | threats <board> | tune <grid | random <n>> <threads> <path>]";
 
 fn main() {
-    let player1 = mcts::MCTS{n: 10};
-    let mut player2 = networks::supervised::SupervisedNetwork::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = args.first().map(String::as_str).unwrap_or("train");
+
+    match command {
+        "train" => {
+            let mut network = networks::supervised::SupervisedNetwork::init();
+            train_network(&mut network);
+        },
+        "match" => {
+            let games = args.get(1).and_then(|n| n.parse().ok()).unwrap_or(10);
+
+            let mut network = networks::supervised::SupervisedNetwork::init();
+            train_network(&mut network);
 
-    train_network(&mut player2);
-    //play_match(player1, player2, 10);
+            print_match(&mcts::MCTS{n: 10}, &network, games);
+        },
+        "dataset" => export_dataset(args.get(1).map(String::as_str).unwrap_or("dataset.csv")),
+        "benchmark" => { ordered::benchmark(&ALL_BOARDS); },
+        "compare" => { pvs::compare(&ALL_BOARDS); },
+        "prove" => {
+            let proof = proofnumber::ProofNumber::init().solve(&board::Board { tiles: [0, 0], turn: 0 });
+            println!("{:?}", proof);
+        },
+        "tablebase" => tablebase(args.get(1).map(String::as_str).unwrap_or("tablebase.bin")),
+        "threats" => {
+            let squares = args.get(1).map(String::as_str).unwrap_or("XO. .X. ..O");
+
+            match board::Board::parse(squares) {
+                Some(board) => threats::analyse(&board),
+                None => println!("A board is {} squares of X, O or .", N2)
+            }
+        },
+        "tune" => tune(&args[1..]),
+        _ => println!("{}", USAGE)
+    }
 }
 
 fn train_network<T>(net: &mut T) where T: networks::network::Network {
@@ -25,41 +50,64 @@ fn train_network<T>(net: &mut T) where T: networks::network::Network {
     net.test();
 }
 
-fn play_match<T, K>(player1: T, player2: K, n: usize) where T: player::Player, K: player::Player {
-    let mut player1_wins = 0;
-    let mut player2_wins = 0;
+// Labels the training boards with every optimal move and writes them
+// to disk, to be loaded back with dataset::load and Network::learn.
+fn export_dataset(path: &str) {
+    use networks::dataset::{self, Label};
+    use networks::supervised::TRAINING_BOARDS;
 
-    for _ in 0..n {
-        match play_game(&player1, &player2) {
-            1  => player1_wins += 1,
-            -1 => player2_wins += 1,
-            _  => ()
-        }
-    }
+    let examples = dataset::generate(&TRAINING_BOARDS, Label::Optimal);
+    dataset::save(&examples, path).expect("Couldn't write the dataset.");
 
-    let draws = n - player1_wins - player2_wins;
-    println!("{} - {} - {}", player1_wins, player2_wins, draws);
+    println!("{} examples written to {}", examples.len(), path);
 }
 
-fn play_game<T, K>(player1: &T, player2: &K) -> isize where T: player::Player, K: player::Player {
-    let mut board = Board { tiles: [0, 0], turn: rand::thread_rng().gen_range(0, 2) };
+// Loads the tablebase from the path, or generates it and saves it there
+// when the file is missing or bad.
+fn tablebase(path: &str) {
+    use tablebase::Tablebase;
 
-    while board.state() == State::Unfinished {
-        let move_;
+    let tablebase = match Tablebase::load(path) {
+        Ok(tablebase) => tablebase,
+        Err(e) => {
+            println!("Generating the tablebase ({})", e);
 
-        if board.turn == PLAYER1 {
-            move_ = player1.best_move(&board);
-        } else {
-            move_ = player2.best_move(&board);
+            let tablebase = Tablebase::generate();
+            tablebase.save(path).expect("Couldn't write the tablebase.");
+            tablebase
         }
+    };
 
-        board.make(move_);
-    }
+    let empty = board::Board { tiles: [0, 0], turn: 0 };
+    println!("{} positions, the empty board is {:?}", tablebase.positions(), tablebase.probe(&empty));
+}
 
-    match board.state() {
-        State::Player1Won => 1,
-        State::Player2Won => -1,
-        State::Draw => 0,
-        _ => panic!("Unfinished state after the game ended.")
-    }
+// Searches the hyperparameters around the supervised network's defaults,
+// every combination or n random ones, and writes the leaderboard.
+fn tune(args: &[String]) {
+    use networks::tuning::{self, Space};
+
+    let space = Space::init();
+
+    let (configs, rest) = match args.first().map(String::as_str) {
+        Some("grid") => (space.grid(), &args[1..]),
+        Some("random") | None => {
+            let n = args.get(1).and_then(|n| n.parse().ok()).unwrap_or(20);
+            (space.sample(n), args.get(2..).unwrap_or(&[]))
+        },
+        Some(_) => return println!("{}", USAGE)
+    };
+
+    let threads = rest.first().and_then(|n| n.parse().ok()).unwrap_or(4);
+    let path = rest.get(1).map(String::as_str).unwrap_or("leaderboard.csv");
+
+    let entries = tuning::run(configs, threads);
+    tuning::write_leaderboard(&entries, path).expect("Couldn't write the leaderboard.");
+
+    println!("{} configurations ranked in {}", entries.len(), path);
+}
+
+fn print_match<T, K>(player1: &T, player2: &K, n: usize) where T: player::Player, K: player::Player {
+    let (player1_wins, player2_wins, draws) = game::play_match(player1, player2, n);
+    println!("{} - {} - {}", player1_wins, player2_wins, draws);
 }
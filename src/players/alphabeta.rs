@@ -5,16 +5,16 @@ use crate::board::{Board, State};
 pub struct AlphaBeta;
 
 impl AlphaBeta {
-    fn search(board: &Board, mut alpha: isize, beta: isize) -> (isize, usize) {
+    pub fn search(board: &Board, mut alpha: isize, beta: isize) -> (isize, usize) {
         if board.state() != State::Unfinished {
             // The game has ended so there is no best move.
-            return (board.score(), 0);
+            return (board.distance_score(), 0);
         }
         
         let mut moves: Vec<usize> = board.gen_moves();
-        assert!(moves.len() > 0);
+        assert!(!moves.is_empty());
 
-        let mut max_score: isize = -2;
+        let mut max_score: isize = -MAX_SCORE;
         let mut best_move: usize = 0;
 
         // Forced moves are precalculated to speed up the search.
@@ -28,7 +28,7 @@ impl AlphaBeta {
         }
 
         for move_ in moves {
-            let mut copy = board.clone();
+            let mut copy = *board;
             copy.make(move_);
 
             let score = -AlphaBeta::search(&copy, -beta, -alpha).0;
@@ -48,15 +48,58 @@ impl AlphaBeta {
             }
         }
 
-        assert!(best_move >= 1 && best_move <= N2);
-        assert!(max_score > -2);
+        assert!((1..=N2).contains(&best_move));
+        assert!(max_score > -MAX_SCORE);
 
-        return (max_score, best_move);
+        (max_score, best_move)
     }
 }
 
 impl Player for AlphaBeta {
     fn best_move(&self, board: &Board) -> usize {
-        AlphaBeta::search(board, -2, 2).1
+        AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE).1
+    }
+
+    // The score of every move, so quicker wins rate higher.
+    fn move_scores(&self, board: &Board) -> Vec<f64> {
+        let mut scores = vec![-(MAX_SCORE as f64); N2];
+
+        for move_ in board.gen_moves() {
+            let mut copy = *board;
+            copy.make(move_);
+
+            scores[move_ - 1] = -AlphaBeta::search(&copy, -MAX_SCORE, MAX_SCORE).0 as f64;
+        }
+
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_takes_immediate_win() {
+        // 7 wins at once, while 5 also wins but a move later.
+        let board = Board { tiles: [0b000001001, 0b000000110], turn: 0 };
+        let (score, move_) = AlphaBeta::search(&board, -MAX_SCORE, MAX_SCORE);
+
+        assert_eq!(move_, 7);
+        assert_eq!(board.moves_to_end(score), Some(1));
+    }
+
+    #[test]
+    fn test_mate_distance() {
+        // After 7 the opponent can only block one of 3 and 9.
+        let mut board = Board { tiles: [0b000010011, 0b010101000], turn: 0 };
+        board.make(7);
+
+        let (score, _) = AlphaBeta::search(&board, -MAX_SCORE, MAX_SCORE);
+        assert!(score < 0);
+        assert_eq!(board.moves_to_end(score), Some(2));
+
+        let empty = Board { tiles: [0, 0], turn: 0 };
+        assert_eq!(AlphaBeta::search(&empty, -MAX_SCORE, MAX_SCORE).0, 0);
     }
 }
@@ -0,0 +1,118 @@
+use crate::constants::*;
+use crate::players::player::Player;
+use crate::players::evaluator::Evaluator;
+use crate::board::{Board, State};
+use crate::threats;
+
+// Alpha-beta that stops after a number of plies and trusts the
+// evaluator's value for the positions it reaches.
+pub struct DepthLimited<E: Evaluator> {
+    pub evaluator: E,
+    pub depth: usize
+}
+
+impl<E: Evaluator> DepthLimited<E> {
+    fn search(&self, board: &Board, depth: usize, mut alpha: f64, beta: f64) -> (f64, usize) {
+        if board.state() != State::Unfinished {
+            // The game has ended so there is no best move.
+            return (board.score() as f64, 0);
+        }
+
+        // Threats see past the depth wherever the win is forced.
+        if let Some(line) = threats::search(board, N2) {
+            return (1.0, line[0]);
+        }
+
+        let (priors, value) = self.evaluator.evaluate(board);
+
+        if depth == 0 {
+            return (value, 0);
+        }
+
+        // Trying the most promising moves first leads to earlier cutoffs.
+        // NaN priors go last rather than panicking.
+        let prior = |sqr: usize| if priors[sqr - 1].is_nan() { f64::NEG_INFINITY } else { priors[sqr - 1] };
+        let mut moves: Vec<usize> = board.gen_moves();
+        moves.sort_by(|&a, &b| prior(b).total_cmp(&prior(a)));
+
+        let mut max_score: f64 = -2.0;
+        let mut best_move: usize = 0;
+
+        for move_ in moves {
+            let mut copy = *board;
+            copy.make(move_);
+
+            let score = -self.search(&copy, depth - 1, -beta, -alpha).0;
+
+            if score > max_score {
+                max_score = score;
+                best_move = move_;
+
+                if max_score >= beta {
+                    break;
+                }
+
+                if max_score > alpha {
+                    alpha = max_score;
+                }
+            }
+        }
+
+        assert!((1..=N2).contains(&best_move));
+
+        (max_score, best_move)
+    }
+}
+
+impl<E: Evaluator> Player for DepthLimited<E> {
+    fn best_move(&self, board: &Board) -> usize {
+        self.search(board, self.depth, -2.0, 2.0).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Blind;
+
+    impl Evaluator for Blind {
+        fn evaluate(&self, _board: &Board) -> ([f64; N2], f64) {
+            ([1.0 / N2 as f64; N2], 0.0)
+        }
+    }
+
+    // A network that has diverged.
+    struct NanPriors;
+
+    impl Evaluator for NanPriors {
+        fn evaluate(&self, _board: &Board) -> ([f64; N2], f64) {
+            ([f64::NAN; N2], 0.0)
+        }
+    }
+
+    #[test]
+    fn test_forced_moves() {
+        let player = DepthLimited { evaluator: Blind, depth: 2 };
+
+        let tests = [
+            (Board { tiles: [0b000000011, 0b000011000], turn: 0 }, 3),
+            (Board { tiles: [0b010000001, 0b000011000], turn: 0 }, 6),
+            (Board { tiles: [0b000010000, 0b000000011], turn: 0 }, 3)
+        ];
+
+        for test in tests.iter() {
+            assert_eq!(player.best_move(&test.0), test.1);
+            assert_eq!(player.best_move(&test.0.inverse()), test.1);
+        }
+    }
+
+    #[test]
+    fn test_nan_priors() {
+        let player = DepthLimited { evaluator: NanPriors, depth: 2 };
+
+        // The blocking move is still found, the ordering just doesn't help.
+        let board = Board { tiles: [0b010000001, 0b000011000], turn: 0 };
+        assert_eq!(player.best_move(&board), 6);
+    }
+}
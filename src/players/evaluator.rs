@@ -0,0 +1,14 @@
+use crate::constants::*;
+use crate::board::Board;
+
+// Anything that can guide a search: a prior for every square
+// and the value of the position for the side to move, between -1 and 1.
+pub trait Evaluator {
+    fn evaluate(&self, board: &Board) -> ([f64; N2], f64);
+}
+
+impl<E: Evaluator> Evaluator for &E {
+    fn evaluate(&self, board: &Board) -> ([f64; N2], f64) {
+        (*self).evaluate(board)
+    }
+}
@@ -1,9 +1,11 @@
 extern crate rand;
 use rand::Rng;
 
+use crate::constants::*;
 use crate::players::player::Player;
 use crate::board::Board;
 use crate::board::State;
+use crate::threats;
 
 pub struct MCTS {
     pub n : usize
@@ -12,50 +14,137 @@ pub struct MCTS {
 struct Node {
     board: Board,
     move_: usize,
+    children: Vec<Node>,
 
+    // Both the reward and the proven value are seen from the perspective
+    // of the player that made move_, which is the one choosing this node.
     reward: f64,
     visits: f64,
+    proven: Option<isize>
 }
 
 impl MCTS {
     fn search(&self, board: &Board) -> usize {
-        let copy = board.clone();
-        let mut root = Node { board: copy, move_: 0, reward: 0.0, visits: 0.0 };
-        let mut children: Vec<Node> = root.expand();
+        // A forced win by threats is played without searching. It's only
+        // looked for at the root, as it would cost more than the rollouts
+        // at every node.
+        if let Some(line) = threats::search(board, N2) {
+            return line[0];
+        }
+
+        self.solve(board)
+    }
+
+    // The tree search itself, which proves wins and losses on its own.
+    fn solve(&self, board: &Board) -> usize {
+        let copy = *board;
+        let mut root = Node::new(copy, 0);
+
+        if root.proven.is_some() {
+            // The game has ended so there is no best move.
+            return 0;
+        }
+
+        let cp: f64 = 0.5_f64.sqrt();
 
         for _ in 0..self.n {
-            let cp: f64 = 0.5_f64.sqrt();
-            let node: &mut Node = root.tree_policy(&mut children, cp);
+            root.iterate(cp);
 
-            root.visits += 1.0;
-            node.random_rollout();
+            // Once the root is solved the best move is already known.
+            if root.proven.is_some() {
+                break;
+            }
         }
 
-        root.tree_policy(&mut children, 0.0).move_
+        root.best_child().move_
     }
 }
 
 impl Node {
+    fn new(board: Board, move_: usize) -> Node {
+        let proven = match board.state() {
+            State::Unfinished => None,
+            // The score is given from the perspective of the side to move,
+            // which is the opponent of the player that made move_.
+            _ => Some(-board.score())
+        };
+
+        Node { board, move_, children: Vec::new(), reward: 0.0, visits: 0.0, proven }
+    }
+
     pub fn expand(&self) -> Vec<Node> {
+        assert!(self.proven.is_none());
+
         let moves: Vec<usize> = self.board.gen_moves();
         let mut nodes: Vec<Node> = Vec::with_capacity(moves.len());
 
         for m in moves {
-            let mut copy = self.board.clone();
+            let mut copy = self.board;
             copy.make(m);
 
-            nodes.push( Node { board: copy, move_: m, reward: 0.0, visits: 0.0 } );
+            nodes.push(Node::new(copy, m));
         }
 
         nodes
     }
 
-    pub fn tree_policy<'a>(&self, children: &'a mut Vec<Node>, cp: f64) -> &'a mut Node {
-        let mut max_score: f64 = std::f64::MIN;
+    // Runs one selection, expansion, simulation and backpropagation step
+    // and returns the reward obtained by the player that made move_.
+    fn iterate(&mut self, cp: f64) -> f64 {
+        let reward = match self.proven {
+            Some(value) => to_reward(value),
+            None => {
+                if self.children.is_empty() {
+                    self.children = self.expand();
+                }
+
+                let child = Node::tree_policy(self.visits, &mut self.children, cp);
+
+                let child_reward = if child.visits == 0.0 && child.proven.is_none() {
+                    child.random_rollout()
+                } else {
+                    child.iterate(cp)
+                };
+
+                self.prove();
+
+                1.0 - child_reward
+            }
+        };
+
+        self.visits += 1.0;
+        self.reward += reward;
+
+        reward
+    }
+
+    // MCTS-Solver: a node is lost if any reply wins for the opponent,
+    // otherwise its value is settled once every reply has been proven.
+    fn prove(&mut self) {
+        let mut best_reply = -1;
+
+        for child in self.children.iter() {
+            match child.proven {
+                Some(1) => {
+                    self.proven = Some(-1);
+                    return;
+                },
+                Some(value) => best_reply = best_reply.max(value),
+                None => best_reply = 2
+            }
+        }
+
+        if best_reply != 2 {
+            self.proven = Some(-best_reply);
+        }
+    }
+
+    pub fn tree_policy(parent_visits: f64, children: &mut Vec<Node>, cp: f64) -> &mut Node {
+        let mut max_score: f64 = f64::MIN;
         let mut best_nodes: Vec<&mut Node> = Vec::with_capacity(children.len());
 
         for child in children {
-            let score = self.ucb_score(child, cp);
+            let score = child.ucb_score(parent_visits, cp);
 
             if score > max_score {
                 max_score = score;
@@ -66,50 +155,143 @@ impl Node {
             }
         }
 
-        assert!(best_nodes.len() > 0);
+        assert!(!best_nodes.is_empty());
 
         let pick: usize = rand::thread_rng().gen_range(0, best_nodes.len()) as usize;
         best_nodes.remove(pick)
     }
 
-    pub fn random_rollout(&mut self) {
-        let mut copy = self.board.clone();
-        let mut state = copy.state();
+    // Proven wins and losses are ranked above and below every average
+    // reward, so a lucky rollout can't be mistaken for a certain win.
+    fn best_child(&self) -> &Node {
+        let mut max_score: f64 = f64::MIN;
+        let mut best_child: Option<&Node> = None;
+
+        for child in self.children.iter() {
+            let score = match child.proven {
+                Some(1) => 2.0,
+                Some(-1) => -1.0,
+                Some(value) => to_reward(value),
+                None if child.visits > 0.0 => child.reward / child.visits,
+                None => 0.0
+            };
+
+            if score > max_score {
+                max_score = score;
+                best_child = Some(child);
+            }
+        }
+
+        best_child.expect("The root has not been expanded.")
+    }
+
+    pub fn random_rollout(&mut self) -> f64 {
+        let mut copy = self.board;
 
-        while state == State::Unfinished {
+        while copy.state() == State::Unfinished {
             let m: usize = copy.random_move();
             copy.make(m);
-
-            state = copy.state();
         }
 
+        // The final score is given from the perspective of copy.turn.
+        let score = if copy.turn == self.board.turn {
+            -copy.score()
+        } else {
+            copy.score()
+        };
+
+        let reward = to_reward(score);
+
         self.visits += 1.0;
+        self.reward += reward;
 
-        match state {
-            State::Draw => self.reward += 0.5,
-            State::Unfinished => panic!("Unfinished random rollout."),
-            _ => {
-                if self.board.turn == copy.turn {
-                    self.reward += 1.0
-                }
-            }
-        }
+        reward
     }
 
-    fn ucb_score(&self, child: &mut Node, cp: f64) -> f64 {
-        if child.visits == 0.0 {
-            return std::f64::MAX;
+    fn ucb_score(&self, parent_visits: f64, cp: f64) -> f64 {
+        if self.visits == 0.0 {
+            return f64::MAX;
         }
-        
-        let exploitation = child.reward / child.visits;
-        let exploration = 2.0 * cp * (2.0 * self.visits.ln() / child.visits).sqrt();
-        
+
+        let exploitation = self.reward / self.visits;
+        let exploration = 2.0 * cp * (2.0 * parent_visits.ln() / self.visits).sqrt();
+
         exploitation + exploration
     }
 }
 
+// Maps a game value of -1, 0 or 1 into a reward between 0 and 1.
+fn to_reward(value: isize) -> f64 {
+    (value + 1) as f64 / 2.0
+}
+
 impl Player for MCTS {
     fn best_move(&self, board: &Board) -> usize {
         self.search(board)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_takes_win() {
+        let mcts = MCTS { n: 1000 };
+
+        let tests = [
+            (Board { tiles: [0b000000011, 0b000011000], turn: 0 }, 3),
+            (Board { tiles: [0b100010000, 0b000000110], turn: 0 }, 1),
+            (Board { tiles: [0b011000100, 0b100100001], turn: 0 }, 5)
+        ];
+
+        for test in tests.iter() {
+            let board = test.0;
+
+            // The same positions are tried with the roles swapped.
+            assert_eq!(mcts.solve(&board), test.1);
+            assert_eq!(mcts.solve(&board.inverse()), test.1);
+        }
+    }
+
+    #[test]
+    fn test_blocks_loss() {
+        let mcts = MCTS { n: 1000 };
+
+        let tests = [
+            (Board { tiles: [0b010000001, 0b000011000], turn: 0 }, 6),
+            (Board { tiles: [0b000010010, 0b100000001], turn: 1 }, 8),
+            (Board { tiles: [0b000010000, 0b000000011], turn: 0 }, 3)
+        ];
+
+        for test in tests.iter() {
+            let board = test.0;
+
+            assert_eq!(mcts.solve(&board), test.1);
+            assert_eq!(mcts.solve(&board.inverse()), test.1);
+        }
+    }
+
+    #[test]
+    fn test_finished_board() {
+        let mcts = MCTS { n: 10 };
+
+        let board = Board { tiles: [0b000000111, 0b000011000], turn: 1 };
+        assert_eq!(mcts.best_move(&board), 0);
+        assert_eq!(mcts.solve(&board), 0);
+
+        // The last empty square is the only move the tree can return.
+        let board = Board { tiles: [0b010101110, 0b101010000], turn: 1 };
+        assert_eq!(mcts.solve(&board), 1);
+    }
+
+    #[test]
+    fn test_threats_shortcut() {
+        // X forks on 4 or 7, found by threat-space search before the
+        // first rollout.
+        let mcts = MCTS { n: 1 };
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+
+        assert_eq!(mcts.best_move(&board), 4);
+    }
+}
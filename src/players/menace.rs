@@ -0,0 +1,170 @@
+extern crate rand;
+use rand::Rng;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::constants::*;
+use crate::board::{Board, State, FULL};
+use crate::symmetries::{transform_move, inverse_symmetry};
+use crate::players::player::Player;
+
+// The box, the bead drawn and the side that drew it.
+type Draw = ((u64, u64), usize, usize);
+
+// Michie's Matchbox Educable Noughts And Crosses Engine: a matchbox of
+// beads for every canonical position (see Board::canonical), one colour
+// per square. Moves are drawn from the box and the beads are reinforced
+// or taken away once the game is over.
+pub struct Menace {
+    pub win_reward: u32,
+    pub draw_reward: u32,
+    pub loss_penalty: u32,
+
+    boxes: RefCell<HashMap<(u64, u64), [u32; N2]>>,
+
+    // Every move of the games in progress.
+    history: RefCell<Vec<Draw>>
+}
+
+impl Menace {
+    pub fn init() -> Menace {
+        Menace {
+            win_reward: 3,
+            draw_reward: 1,
+            loss_penalty: 1,
+            boxes: RefCell::new(HashMap::new()),
+            history: RefCell::new(Vec::new())
+        }
+    }
+
+    // A new box has fewer beads the later the position is in the game,
+    // so that the last moves are learnt quicker.
+    fn new_box(key: (u64, u64)) -> [u32; N2] {
+        let empty = FULL ^ key.0 ^ key.1;
+        let ply = (key.0 | key.1).count_ones();
+        let beads = if ply < 6 { 4 - ply / 2 } else { 1 };
+
+        let mut matchbox: [u32; N2] = [0; N2];
+
+        for (i, square) in matchbox.iter_mut().enumerate() {
+            if empty & (1 << i) != 0 {
+                *square = beads;
+            }
+        }
+
+        matchbox
+    }
+
+    // The beads in the box of the position, placed on the board's squares.
+    pub fn beads(&self, board: &Board) -> [u32; N2] {
+        let (key, symmetry) = board.canonical();
+
+        let matchbox = match self.boxes.borrow().get(&key) {
+            Some(matchbox) => *matchbox,
+            None => Menace::new_box(key)
+        };
+
+        let mut beads: [u32; N2] = [0; N2];
+
+        for i in 0..N2 {
+            beads[transform_move(i + 1, inverse_symmetry(symmetry)) - 1] = matchbox[i];
+        }
+
+        beads
+    }
+
+    fn draw(&self, board: &Board) -> usize {
+        let (key, symmetry) = board.canonical();
+        let mut boxes = self.boxes.borrow_mut();
+
+        let matchbox = boxes.entry(key).or_insert_with(|| Menace::new_box(key));
+
+        // An empty box is refilled instead of resigning.
+        if matchbox.iter().sum::<u32>() == 0 {
+            *matchbox = Menace::new_box(key);
+        }
+
+        let total: u32 = matchbox.iter().sum();
+        let mut pick = rand::thread_rng().gen_range(0, total);
+        let mut bead = 0;
+
+        while pick >= matchbox[bead] {
+            pick -= matchbox[bead];
+            bead += 1;
+        }
+
+        self.history.borrow_mut().push((key, bead + 1, board.turn));
+
+        transform_move(bead + 1, inverse_symmetry(symmetry))
+    }
+}
+
+impl Player for Menace {
+    fn best_move(&self, board: &Board) -> usize {
+        self.draw(board)
+    }
+
+    fn game_over(&self, board: &Board) {
+        // An unfinished board was forfeited by the side to move, as the
+        // Env does after an illegal move.
+        let state = board.state();
+
+        let mut boxes = self.boxes.borrow_mut();
+
+        for (key, move_, turn) in self.history.borrow_mut().drain(..) {
+            let beads = &mut boxes.get_mut(&key).unwrap()[move_ - 1];
+
+            // The last player to move is the one who won.
+            if state == State::Draw {
+                *beads += self.draw_reward;
+            } else if turn != board.turn {
+                *beads += self.win_reward;
+            } else {
+                *beads -= self.loss_penalty.min(*beads);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reinforcement() {
+        let menace = Menace::init();
+
+        // Only the winning square is left empty.
+        let board = Board { tiles: [0b001100011, 0b110011000], turn: 0 };
+        let initial = menace.beads(&board)[2];
+        let move_ = menace.best_move(&board);
+
+        assert_eq!(move_, 3);
+
+        let mut end = board;
+        end.make(move_);
+        menace.game_over(&end);
+
+        assert_eq!(menace.beads(&board)[2], initial + menace.win_reward);
+    }
+
+    #[test]
+    fn test_punishment_under_symmetry() {
+        let menace = Menace::init();
+        let board = Board { tiles: [0b000000011, 0b000011000], turn: 0 };
+
+        // Whatever is drawn on one image of the board is punished
+        // on all of them.
+        let image = board.transform(5);
+        let move_ = menace.best_move(&image);
+        let before = menace.beads(&image)[move_ - 1];
+
+        let end = Board { tiles: [0b000000011, 0b000111000], turn: 0 };
+        menace.game_over(&end.transform(5));
+
+        let beads = menace.beads(&board);
+        assert_eq!(beads[transform_move(move_, 5) - 1], before - 1);
+        assert_eq!(menace.beads(&image)[move_ - 1], before - 1);
+    }
+}
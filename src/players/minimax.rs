@@ -7,17 +7,17 @@ pub struct Minimax;
 impl Minimax {
     fn search(board: &Board) -> (isize, usize) {
         if board.state() != State::Unfinished {
-            return (board.score(), 0);
+            return (board.distance_score(), 0);
         }
         
         let moves: Vec<usize> = board.gen_moves();
-        assert!(moves.len() > 0);
+        assert!(!moves.is_empty());
 
-        let mut max_score: isize = -2;
+        let mut max_score: isize = -MAX_SCORE;
         let mut best_move: usize = 0;
 
         for move_ in moves {
-            let mut copy = board.clone();
+            let mut copy = *board;
             copy.make(move_);
 
             let (mut score, _) = Minimax::search(&copy);
@@ -29,10 +29,10 @@ impl Minimax {
             }
         }
 
-        assert!(best_move >= 1 && best_move <= N2);
-        assert!(max_score > -2);
+        assert!((1..=N2).contains(&best_move));
+        assert!(max_score > -MAX_SCORE);
 
-        return (max_score, best_move);
+        (max_score, best_move)
     }
 }
 
@@ -41,3 +41,22 @@ impl Player for Minimax {
         Minimax::search(board).1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_takes_immediate_win() {
+        // 5 comes first and wins too, but only a move later than 7.
+        let board = Board { tiles: [0b000001001, 0b000000110], turn: 0 };
+        let (score, move_) = Minimax::search(&board);
+
+        assert_eq!(move_, 7);
+        assert_eq!(board.moves_to_end(score), Some(1));
+
+        let mut copy = board;
+        copy.make(5);
+        assert_eq!(copy.moves_to_end(Minimax::search(&copy).0), Some(2));
+    }
+}
@@ -1,8 +1,20 @@
 pub mod player;
+pub mod evaluator;
 
 pub mod human;
 pub mod minimax;
 pub mod alphabeta;
+pub mod ordered;
+pub mod pvs;
+pub mod proofnumber;
+pub mod rules;
+pub mod random;
+pub mod oracle;
+pub mod tablebase;
 pub mod mcts;
+pub mod puct;
+pub mod depthlimited;
+pub mod qlearning;
+pub mod menace;
 
 pub mod networks;
\ No newline at end of file
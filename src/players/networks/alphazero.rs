@@ -0,0 +1,269 @@
+extern crate rand;
+use rand::Rng;
+
+use std::collections::VecDeque;
+use std::io;
+
+use crate::constants::*;
+use crate::game;
+
+use crate::board::{Board, State, PLAYER1};
+
+use crate::players::{
+    player::Player,
+    networks::network::Network,
+    networks::policyvalue::{PolicyValueNetwork, Example},
+    networks::metrics::{self, Metrics},
+    networks::supervised::VALIDATION_BOARDS,
+    alphabeta::AlphaBeta,
+    puct::{self, PUCT}
+};
+
+pub struct AlphaZero {
+    pub net: PolicyValueNetwork,
+
+    pub iterations: u32,
+    pub games: u32,
+    pub simulations: usize,
+    pub c_puct: f64,
+    pub noise: f64,
+
+    // The first moves of every game are sampled from the visit shares
+    // so that self-play and gating games don't repeat themselves.
+    pub sampled_moves: usize,
+
+    pub buffer_size: usize,
+    pub batch_size: usize,
+
+    // Share of the points a candidate needs against the current best
+    // network to replace it.
+    pub gating_games: u32,
+    pub gating_threshold: f64,
+
+    pub checkpoint: Option<String>,
+
+    buffer: VecDeque<Example>
+}
+
+impl AlphaZero {
+    pub fn init() -> AlphaZero {
+        let mut net = PolicyValueNetwork::init();
+        net.epochs = 5;
+
+        AlphaZero {
+            net,
+            iterations: 20,
+            games: 25,
+            simulations: 50,
+            c_puct: 1.5,
+            noise: 0.25,
+            sampled_moves: 2,
+            buffer_size: 5000,
+            batch_size: 1000,
+            gating_games: 20,
+            gating_threshold: 0.55,
+            checkpoint: None,
+            buffer: VecDeque::new()
+        }
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        self.net.save(path)
+    }
+
+    pub fn load(&mut self, path: &str) -> io::Result<()> {
+        self.net.load(path)
+    }
+
+    // Plays a game of the network against itself and labels every
+    // position with the search policy and the final outcome.
+    fn self_play(&self) -> Vec<Example> {
+        let mut board = Board { tiles: [0, 0], turn: PLAYER1 };
+        let mut history: Vec<(Vec<f64>, [f64; N2], usize)> = Vec::new();
+
+        while board.state() == State::Unfinished {
+            let policy = puct::search(&self.net, &board, self.simulations, self.c_puct, self.noise);
+            let move_ = pick_move(&board, &policy, history.len() < self.sampled_moves);
+
+            history.push((board.to_binary().to_vec(), policy, board.turn));
+            board.make(move_);
+        }
+
+        // The final score is given from the perspective of board.turn.
+        let score = board.score() as f64;
+        let mut examples = Vec::with_capacity(history.len());
+
+        for (input, policy, turn) in history {
+            let value = if turn == board.turn { score } else { -score };
+
+            examples.push((input, PolicyValueNetwork::target(&policy, value)));
+        }
+
+        examples
+    }
+
+    fn sample_batch(&self) -> Vec<Example> {
+        let mut rng = rand::thread_rng();
+
+        if self.buffer.len() <= self.batch_size {
+            return self.buffer.iter().cloned().collect();
+        }
+
+        (0..self.batch_size)
+            .map(|_| self.buffer[rng.gen_range(0, self.buffer.len())].clone())
+            .collect()
+    }
+
+    // Plays the candidate against the current best network, alternating
+    // who starts, and returns its share of the points.
+    fn gate(&self, candidate: &PolicyValueNetwork) -> f64 {
+        let mut points = 0.0;
+
+        for game in 0..self.gating_games {
+            let mut board = Board { tiles: [0, 0], turn: (game % 2) as usize };
+            let mut ply = 0;
+
+            while board.state() == State::Unfinished {
+                // The candidate always plays as PLAYER1.
+                let evaluator = if board.turn == PLAYER1 { candidate } else { &self.net };
+
+                let policy = puct::search(evaluator, &board, self.simulations, self.c_puct, 0.0);
+                board.make(pick_move(&board, &policy, ply < self.sampled_moves));
+
+                ply += 1;
+            }
+
+            match board.state() {
+                State::Player1Won => points += 1.0,
+                State::Draw => points += 0.5,
+                _ => ()
+            }
+        }
+
+        points / self.gating_games as f64
+    }
+}
+
+impl Network for AlphaZero {
+    // The share of visits of every square after searching.
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        puct::search(&self.net, board, self.simulations, self.c_puct, 0.0).to_vec()
+    }
+
+    fn play(&self, board: &Board) -> usize {
+        let player = PUCT { evaluator: &self.net, n: self.simulations, c_puct: self.c_puct };
+        player.best_move(board)
+    }
+
+    // Trains the network straight on policy and value targets, the way
+    // PolicyValueNetwork does, without any self-play.
+    fn learn(&mut self, examples: &[Example]) {
+        self.net.learn(examples);
+    }
+
+    fn train(&mut self) {
+        for iteration in 0..self.iterations {
+            for _ in 0..self.games {
+                for example in self.self_play() {
+                    if self.buffer.len() == self.buffer_size {
+                        self.buffer.pop_front();
+                    }
+
+                    self.buffer.push_back(example);
+                }
+            }
+
+            let batch = self.sample_batch();
+            let mut candidate = self.net.clone();
+            candidate.fit(&batch);
+
+            let score = self.gate(&candidate);
+            let accepted = score >= self.gating_threshold;
+
+            println!("Iteration {}: candidate scored {:.1} % ({})",
+                iteration + 1, score * 100.0, if accepted { "accepted" } else { "rejected" });
+
+            if accepted {
+                self.net = candidate;
+
+                if let Some(path) = &self.checkpoint {
+                    self.save(path).expect("Couldn't save the checkpoint.");
+                }
+            }
+        }
+    }
+
+    // Compares the searched moves with perfect play and then plays
+    // against it, where the best possible result is a draw.
+    fn test(&self) -> Metrics {
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+        println!("{}", metrics);
+
+        let ab = AlphaBeta{};
+        let n = 20;
+
+        let mut draws = 0;
+        let mut losses = 0;
+
+        for game in 0..n {
+            let mut board = Board { tiles: [0, 0], turn: game % 2 };
+
+            while board.state() == State::Unfinished {
+                let move_ = if board.turn == PLAYER1 {
+                    self.play(&board)
+                } else {
+                    ab.best_move(&board)
+                };
+
+                board.make(move_);
+            }
+
+            match board.state() {
+                State::Draw => draws += 1,
+                State::Player2Won => losses += 1,
+                _ => ()
+            }
+        }
+
+        println!("Against AlphaBeta: {} draws, {} losses", draws, losses);
+
+        metrics
+    }
+}
+
+// Samples a move in proportion to the policy or takes the most visited one.
+fn pick_move(board: &Board, policy: &[f64; N2], sample: bool) -> usize {
+    if sample {
+        game::sample_move(policy)
+    } else {
+        game::best_legal_move(board, policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_play_targets() {
+        let mut az = AlphaZero::init();
+        az.simulations = 10;
+
+        let examples = az.self_play();
+        assert!(examples.len() >= 5 && examples.len() <= N2);
+
+        for (input, target) in examples.iter() {
+            assert_eq!(input.len(), N2 * 3);
+            assert_eq!(target.len(), N2 + 1);
+
+            let total: f64 = target[..N2].iter().sum();
+            assert!((total - 1.0).abs() < 1e-9);
+
+            let value = target[N2];
+            assert!(value == -1.0 || value == 0.0 || value == 1.0);
+        }
+
+        // Consecutive positions belong to opposite sides.
+        assert_eq!(examples[0].1[N2], -examples[1].1[N2]);
+    }
+}
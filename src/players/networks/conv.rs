@@ -0,0 +1,386 @@
+extern crate rand;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::constants::*;
+use crate::game;
+
+use crate::board::Board;
+
+use crate::players::{
+    networks::network::Network,
+    networks::neural::{self, Activation, Loss, Optimiser, Example},
+    networks::dataset::Label,
+    networks::metrics::{self, Metrics},
+    networks::training::{Schedule, TrainingLog, Record},
+    networks::supervised::{self, VALIDATION_BOARDS}
+};
+
+// A convolution over square planes of any size. The borders are padded
+// with zeros so the planes keep their size from layer to layer.
+#[derive(Clone)]
+pub struct ConvLayer {
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub kernel: usize,
+    pub activation: Activation,
+
+    // Indexed by output channel, input channel, row and column.
+    pub weights: Vec<f64>,
+    pub biases: Vec<f64>,
+
+    moments: [Vec<f64>; 4]
+}
+
+// Fully convolutional: the weights don't depend on the size of the board,
+// so a network trained on 3x3 can be run on 4x4 or 5x5 planes. The last
+// layer has a single channel, the score of every square, and a softmax
+// over all of them gives the move distribution.
+#[derive(Clone)]
+pub struct ConvNetwork {
+    pub layers: Vec<ConvLayer>,
+    pub optimiser: Optimiser,
+    pub augment: bool,
+    pub average_symmetries: bool,
+    pub rounds: usize,
+    pub epochs: u32,
+    pub batch_size: usize,
+    pub learning_rate: f64,
+    pub schedule: Schedule,
+    pub log: TrainingLog,
+    steps: i32
+}
+
+struct Gradients {
+    weights: Vec<Vec<f64>>,
+    biases: Vec<Vec<f64>>
+}
+
+impl ConvLayer {
+    pub fn new(in_channels: usize, out_channels: usize, kernel: usize, activation: Activation) -> ConvLayer {
+        assert!(kernel % 2 == 1, "Kernels must have odd sizes to be centred.");
+
+        let mut rng = rand::thread_rng();
+
+        // He initialisation over the inputs that every output sees.
+        let fan_in = in_channels * kernel * kernel;
+        let limit = (6.0 / fan_in as f64).sqrt();
+        let n_weights = out_channels * fan_in;
+
+        ConvLayer {
+            in_channels,
+            out_channels,
+            kernel,
+            activation,
+            weights: (0..n_weights).map(|_| rng.gen_range(-limit, limit)).collect(),
+            biases: vec![0.0; out_channels],
+            moments: [vec![0.0; n_weights], vec![0.0; n_weights], vec![0.0; out_channels], vec![0.0; out_channels]]
+        }
+    }
+
+    fn weight(&self, o: usize, c: usize, dy: usize, dx: usize) -> usize {
+        ((o * self.in_channels + c) * self.kernel + dy) * self.kernel + dx
+    }
+
+    // Calls f with the output square, the input square and the kernel
+    // offset of every pair that the convolution connects.
+    fn connections<F>(&self, size: usize, mut f: F) where F: FnMut(usize, usize, usize, usize) {
+        let pad = (self.kernel / 2) as isize;
+
+        for y in 0..size {
+            for x in 0..size {
+                for dy in 0..self.kernel {
+                    for dx in 0..self.kernel {
+                        let iy = y as isize + dy as isize - pad;
+                        let ix = x as isize + dx as isize - pad;
+
+                        if iy >= 0 && ix >= 0 && (iy as usize) < size && (ix as usize) < size {
+                            f(y * size + x, iy as usize * size + ix as usize, dy, dx);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn forward(&self, input: &[f64], size: usize) -> Vec<f64> {
+        let area = size * size;
+        assert_eq!(input.len(), self.in_channels * area);
+
+        let mut output = vec![0.0; self.out_channels * area];
+
+        for o in 0..self.out_channels {
+            for sqr in 0..area {
+                output[o * area + sqr] = self.biases[o];
+            }
+
+            for c in 0..self.in_channels {
+                self.connections(size, |out_sqr, in_sqr, dy, dx| {
+                    output[o * area + out_sqr] += self.weights[self.weight(o, c, dy, dx)] * input[c * area + in_sqr];
+                });
+            }
+        }
+
+        // The final softmax goes over every square, not per channel.
+        if self.activation != Activation::Softmax {
+            self.activation.apply(&mut output);
+        }
+
+        output
+    }
+
+    // Adds the gradients given the delta of the pre-activations and returns
+    // the gradient with respect to the input.
+    fn backward(&self, input: &[f64], delta: &[f64], size: usize,
+                weights: &mut [f64], biases: &mut [f64]) -> Vec<f64> {
+        let area = size * size;
+        let mut grad_input = vec![0.0; input.len()];
+
+        for o in 0..self.out_channels {
+            for sqr in 0..area {
+                biases[o] += delta[o * area + sqr];
+            }
+
+            for c in 0..self.in_channels {
+                self.connections(size, |out_sqr, in_sqr, dy, dx| {
+                    let w = self.weight(o, c, dy, dx);
+                    let d = delta[o * area + out_sqr];
+
+                    weights[w] += d * input[c * area + in_sqr];
+                    grad_input[c * area + in_sqr] += d * self.weights[w];
+                });
+            }
+        }
+
+        grad_input
+    }
+}
+
+impl ConvNetwork {
+    pub fn init() -> ConvNetwork {
+        ConvNetwork {
+            layers: vec![
+                ConvLayer::new(3, 16, 3, Activation::Relu),
+                ConvLayer::new(16, 16, 3, Activation::Relu),
+                ConvLayer::new(16, 16, 3, Activation::Relu),
+                ConvLayer::new(16, 1, 1, Activation::Softmax)
+            ],
+            optimiser: Optimiser::adam(),
+            augment: false,
+            average_symmetries: false,
+            rounds: 50,
+            epochs: 1,
+            batch_size: 32,
+            learning_rate: 0.003,
+            schedule: Schedule::Exponential(0.95),
+            log: TrainingLog::init(),
+            steps: 0
+        }
+    }
+
+    // Takes the three planes of Board::to_binary, for a board of any size,
+    // and returns the input followed by the output of every layer.
+    pub fn forward(&self, planes: &[f64], size: usize) -> Vec<Vec<f64>> {
+        let mut activations = vec![planes.to_vec()];
+
+        for layer in self.layers.iter() {
+            let output = layer.forward(activations.last().unwrap(), size);
+            activations.push(output);
+        }
+
+        // Left to the network by the final layer (see ConvLayer::forward).
+        if self.output_activation() == Activation::Softmax {
+            Activation::Softmax.apply(activations.last_mut().unwrap());
+        }
+
+        activations
+    }
+
+    fn output_activation(&self) -> Activation {
+        self.layers.last().unwrap().activation
+    }
+
+    // The move distribution over the size * size squares.
+    pub fn run(&self, planes: &[f64], size: usize) -> Vec<f64> {
+        self.forward(planes, size).pop().unwrap()
+    }
+
+    // One pass over the examples in shuffled mini-batches, with
+    // cross-entropy against the target distributions.
+    pub fn fit(&mut self, examples: &[(Vec<f64>, Vec<f64>)], size: usize, rate: f64) -> f64 {
+        let mut order: Vec<usize> = (0..examples.len()).collect();
+        order.shuffle(&mut rand::thread_rng());
+
+        let mut total_loss = 0.0;
+
+        for batch in order.chunks(self.batch_size) {
+            let mut gradients = Gradients {
+                weights: self.layers.iter().map(|l| vec![0.0; l.weights.len()]).collect(),
+                biases: self.layers.iter().map(|l| vec![0.0; l.biases.len()]).collect()
+            };
+
+            for &i in batch {
+                let (input, target) = &examples[i];
+                let activations = self.forward(input, size);
+                let output = activations.last().unwrap();
+
+                let activation = self.output_activation();
+                total_loss += Loss::CrossEntropy.value(activation, output, target);
+
+                let mut delta = Loss::CrossEntropy.delta(activation, output, target);
+
+                for l in (0..self.layers.len()).rev() {
+                    let grad = self.layers[l].backward(&activations[l], &delta, size,
+                        &mut gradients.weights[l], &mut gradients.biases[l]);
+
+                    if l > 0 {
+                        delta = self.layers[l - 1].activation.backward(&activations[l], &grad);
+                    }
+                }
+            }
+
+            self.steps += 1;
+
+            let scale = 1.0 / batch.len() as f64;
+            let (optimiser, steps) = (self.optimiser, self.steps);
+
+            for (l, layer) in self.layers.iter_mut().enumerate() {
+                let (weight_moments, bias_moments) = layer.moments.split_at_mut(2);
+
+                neural::update(&mut layer.weights, &gradients.weights[l], weight_moments, optimiser, rate, scale, steps);
+                neural::update(&mut layer.biases, &gradients.biases[l], bias_moments, optimiser, rate, scale, steps);
+            }
+        }
+
+        total_loss / examples.len() as f64
+    }
+}
+
+impl Network for ConvNetwork {
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        self.run(&board.to_binary(), N)
+    }
+
+    fn play(&self, board: &Board) -> usize {
+        let output = if self.average_symmetries {
+            self.symmetric_scores(board)
+        } else {
+            self.scores(board)
+        };
+
+        game::best_legal_move(board, &output)
+    }
+
+    fn train(&mut self) {
+        let data = supervised::training_data(self.augment, |board| Label::BestMove.target(board));
+        self.learn(&data);
+    }
+
+    // The inputs are read as 3x3 planes.
+    fn learn(&mut self, data: &[Example]) {
+        self.log.clear();
+
+        for round in 0..self.rounds {
+            let lr = self.schedule.rate(self.learning_rate, round, self.rounds);
+
+            for epoch in 0..self.epochs as usize {
+                let loss = self.fit(data, N, lr);
+
+                if self.log.active() {
+                    let accuracy = metrics::accuracy(self, &VALIDATION_BOARDS);
+                    let epoch = round * self.epochs as usize + epoch + 1;
+
+                    self.log.record(Record { epoch, loss, accuracy, learning_rate: lr })
+                        .expect("Couldn't write the training log.");
+                }
+            }
+        }
+    }
+
+    fn test(&self) -> Metrics {
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+        println!("{}", metrics);
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The planes of an empty board of the given size.
+    fn empty_planes(size: usize) -> Vec<f64> {
+        let area = size * size;
+        let mut planes = vec![0.0; 3 * area];
+
+        for p in planes[2 * area..].iter_mut() {
+            *p = 1.0;
+        }
+
+        planes
+    }
+
+    #[test]
+    fn test_any_size() {
+        let net = ConvNetwork::init();
+
+        for size in 3..6 {
+            let output = net.run(&empty_planes(size), size);
+            let total: f64 = output.iter().sum();
+
+            assert_eq!(output.len(), size * size);
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+
+        // A sigmoid output scores every square on its own.
+        let mut net = ConvNetwork::init();
+        net.layers.pop();
+        net.layers.push(ConvLayer::new(16, 1, 1, Activation::Sigmoid));
+
+        let output = net.run(&empty_planes(N), N);
+        assert!(output.iter().all(|&o| o > 0.0 && o < 1.0));
+        assert!((output.iter().sum::<f64>() - 1.0).abs() > 1e-9);
+    }
+
+    #[test]
+    fn test_gradients() {
+        const H: f64 = 1e-6;
+
+        let mut net = ConvNetwork::init();
+        net.layers.truncate(1);
+        net.layers.push(ConvLayer::new(16, 1, 3, Activation::Softmax));
+
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+        let input = board.to_binary().to_vec();
+        let mut target = vec![0.0; N2];
+        target[2] = 1.0;
+
+        let activations = net.forward(&input, N);
+        let mut delta = Loss::CrossEntropy.delta(Activation::Softmax, activations.last().unwrap(), &target);
+
+        let mut weights: Vec<Vec<f64>> = net.layers.iter().map(|l| vec![0.0; l.weights.len()]).collect();
+        let mut biases: Vec<Vec<f64>> = net.layers.iter().map(|l| vec![0.0; l.biases.len()]).collect();
+
+        for l in (0..net.layers.len()).rev() {
+            let grad = net.layers[l].backward(&activations[l], &delta, N, &mut weights[l], &mut biases[l]);
+
+            if l > 0 {
+                delta = net.layers[l - 1].activation.backward(&activations[l], &grad);
+            }
+        }
+
+        for (l, grads) in weights.iter().enumerate() {
+            for i in (0..grads.len()).step_by(7) {
+                let original = net.layers[l].weights[i];
+
+                net.layers[l].weights[i] = original + H;
+                let plus = Loss::CrossEntropy.value(Activation::Softmax, &net.run(&input, N), &target);
+                net.layers[l].weights[i] = original - H;
+                let minus = Loss::CrossEntropy.value(Activation::Softmax, &net.run(&input, N), &target);
+                net.layers[l].weights[i] = original;
+
+                assert!(((plus - minus) / (2.0 * H) - grads[i]).abs() < 1e-5);
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+extern crate rand;
+use rand::Rng;
+
+use std::fs;
+use std::io;
+
+use crate::constants::*;
+
+use crate::board::{Board, State};
+
+use crate::players::{
+    player::Player,
+    networks::neural::Example,
+    alphabeta::AlphaBeta,
+    oracle::Oracle
+};
+
+// What a position is labelled with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Label {
+    // A one on the move AlphaBeta plays.
+    BestMove,
+
+    // An even share on every optimal move.
+    Optimal,
+
+    // AlphaBeta's move followed by the score of the position, the
+    // target of the PolicyValueNetwork.
+    PolicyValue
+}
+
+impl Label {
+    pub fn target(&self, board: &Board) -> Vec<f64> {
+        match self {
+            Label::BestMove | Label::PolicyValue => {
+                let (score, move_) = AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE);
+
+                let mut target = vec![0.0; N2];
+                target[move_ - 1] = 1.0;
+
+                if *self == Label::PolicyValue {
+                    target.push(score.signum() as f64);
+                }
+
+                target
+            },
+            Label::Optimal => Oracle::soft_target(board).to_vec()
+        }
+    }
+}
+
+// Every unfinished position of games played with random moves.
+pub fn random_boards(games: usize) -> Vec<Board> {
+    let mut rng = rand::thread_rng();
+    let mut boards = Vec::new();
+
+    for _ in 0..games {
+        let mut board = Board { tiles: [0, 0], turn: rng.gen_range(0, 2) };
+
+        while board.state() == State::Unfinished {
+            boards.push(board);
+            board.make(board.random_move());
+        }
+    }
+
+    boards
+}
+
+// Every unfinished position of games the player plays against itself.
+pub fn self_play_boards<T: Player + ?Sized>(player: &T, games: usize) -> Vec<Board> {
+    let mut boards = Vec::new();
+
+    for _ in 0..games {
+        let mut board = Board { tiles: [0, 0], turn: rand::thread_rng().gen_range(0, 2) };
+
+        while board.state() == State::Unfinished {
+            boards.push(board);
+            board.make(player.best_move(&board));
+        }
+
+        player.game_over(&board);
+    }
+
+    boards
+}
+
+pub fn generate(boards: &[Board], label: Label) -> Vec<Example> {
+    boards.iter().map(|board| (board.to_binary().to_vec(), label.target(board))).collect()
+}
+
+// A CSV file. The header names the columns: x0 to x26 for the input,
+// the three planes of Board::to_binary, and y0 to y8 for the target over
+// the squares, with a y9 for the value if there's one. Then a line per
+// example.
+pub fn save(examples: &[Example], path: &str) -> io::Result<()> {
+    assert!(!examples.is_empty());
+
+    let (inputs, targets) = (examples[0].0.len(), examples[0].1.len());
+
+    let mut columns: Vec<String> = (0..inputs).map(|i| format!("x{}", i)).collect();
+    columns.extend((0..targets).map(|i| format!("y{}", i)));
+
+    let mut contents = columns.join(",");
+    contents.push('\n');
+
+    for (input, target) in examples.iter() {
+        assert!(input.len() == inputs && target.len() == targets);
+
+        let values: Vec<String> = input.iter().chain(target.iter()).map(|x| x.to_string()).collect();
+        contents.push_str(&values.join(","));
+        contents.push('\n');
+    }
+
+    fs::write(path, contents)
+}
+
+pub fn load(path: &str) -> io::Result<Vec<Example>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    let contents = fs::read_to_string(path)?;
+    let mut lines = contents.lines();
+
+    let header = lines.next().ok_or_else(|| invalid("The dataset is empty."))?;
+    let inputs = header.split(',').filter(|column| column.starts_with('x')).count();
+
+    let mut examples = Vec::new();
+
+    for line in lines {
+        let values = line.split(',')
+            .map(|x| x.parse::<f64>().map_err(|_| invalid("Couldn't parse a value.")))
+            .collect::<io::Result<Vec<f64>>>()?;
+
+        if values.len() != header.split(',').count() {
+            return Err(invalid("An example has the wrong number of values."));
+        }
+
+        examples.push((values[..inputs].to_vec(), values[inputs..].to_vec()));
+    }
+
+    Ok(examples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let boards = random_boards(5);
+        let examples = generate(&boards, Label::PolicyValue);
+
+        let path = std::env::temp_dir().join("dataset_test.csv");
+        let path = path.to_str().unwrap();
+
+        save(&examples, path).unwrap();
+        let loaded = load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded, examples);
+        assert_eq!(loaded[0].0.len(), N2 * 3);
+        assert_eq!(loaded[0].1.len(), N2 + 1);
+    }
+
+    #[test]
+    fn test_labels() {
+        // 3 and 9 win at once, 7 a move later.
+        let board = Board { tiles: [0b000010011, 0b010101000], turn: 0 };
+        let optimal = Label::Optimal.target(&board);
+
+        assert_eq!(Label::BestMove.target(&board).iter().sum::<f64>(), 1.0);
+        assert_eq!(Label::PolicyValue.target(&board)[N2], 1.0);
+        assert!(optimal[2] > 0.0 && optimal[6] > 0.0 && optimal[8] > 0.0);
+
+        for board in self_play_boards(&AlphaBeta{}, 3) {
+            assert!(board.state() == State::Unfinished);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+extern crate rand;
+use rand::Rng;
+
+use std::fs;
+use std::io;
+
+use crate::constants::*;
+
+use crate::board::Board;
+use crate::game::{self, play_match};
+
+use crate::players::{
+    player::Player,
+    networks::network::Network,
+    networks::neural::{NN, Activation, Loss, Init, Example},
+    networks::metrics::{self, Metrics},
+    networks::supervised::VALIDATION_BOARDS,
+    alphabeta::AlphaBeta,
+    mcts::MCTS
+};
+
+// Evolves the weights of a network instead of training them: every
+// generation the individuals play matches against MCTS and AlphaBeta, the
+// fittest pass on as they are and the rest of the population is bred from
+// them by crossover and mutation. The network kept is the best one found.
+pub struct EvolvedNetwork {
+    pub nn: NN,
+    pub population: usize,
+    pub generations: usize,
+
+    // Games against each opponent to measure the fitness.
+    pub games: usize,
+    pub mcts_iterations: usize,
+
+    // Individuals copied to the next generation untouched.
+    pub elite: usize,
+
+    // Parents are the fittest of this many individuals drawn at random.
+    pub tournament: usize,
+
+    // The chance of every weight being mutated and by how much at most.
+    pub mutation_rate: f64,
+    pub mutation_size: f64
+}
+
+// A member of the population, playing its best legal move.
+struct Individual {
+    nn: NN,
+    fitness: f64
+}
+
+impl Player for Individual {
+    fn best_move(&self, board: &Board) -> usize {
+        game::best_legal_move(board, &self.nn.run(&board.to_binary()))
+    }
+}
+
+impl EvolvedNetwork {
+    pub fn init() -> EvolvedNetwork {
+        EvolvedNetwork {
+            nn: EvolvedNetwork::new_nn(),
+            population: 30,
+            generations: 30,
+            games: 10,
+            mcts_iterations: 100,
+            elite: 4,
+            tournament: 3,
+            mutation_rate: 0.1,
+            mutation_size: 0.3
+        }
+    }
+
+    fn new_nn() -> NN {
+        let mut nn = NN::new(&[27, 27, N2], Activation::Tanh, Activation::Softmax, Init::Xavier);
+        nn.loss = Loss::CrossEntropy;
+        nn
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.nn.to_text())
+    }
+
+    pub fn load(&mut self, path: &str) -> io::Result<()> {
+        self.nn = NN::from_text(&fs::read_to_string(path)?)?;
+        Ok(())
+    }
+
+    // The share of points against each opponent, where a win is worth
+    // one and a draw a half, averaged over both.
+    fn fitness(&self, nn: &NN) -> f64 {
+        let individual = Individual { nn: nn.clone(), fitness: 0.0 };
+        let mcts = MCTS { n: self.mcts_iterations };
+        let ab = AlphaBeta{};
+
+        let (wins, _, draws) = play_match(&individual, &mcts, self.games);
+        let mut points = wins as f64 + draws as f64 / 2.0;
+
+        let (wins, _, draws) = play_match(&individual, &ab, self.games);
+        points += wins as f64 + draws as f64 / 2.0;
+
+        points / (2 * self.games) as f64
+    }
+
+    fn select<'a>(&self, population: &'a [Individual]) -> &'a Individual {
+        let mut rng = rand::thread_rng();
+        let mut best = &population[rng.gen_range(0, population.len())];
+
+        for _ in 1..self.tournament {
+            let other = &population[rng.gen_range(0, population.len())];
+
+            if other.fitness > best.fitness {
+                best = other;
+            }
+        }
+
+        best
+    }
+
+    // Every weight is taken from either parent and then maybe mutated.
+    fn breed(&self, parent1: &NN, parent2: &NN) -> NN {
+        let mut rng = rand::thread_rng();
+        let mut child = parent1.clone();
+
+        for (l, layer) in child.layers.iter_mut().enumerate() {
+            let other = &parent2.layers[l];
+            let params = layer.weights.iter_mut().zip(other.weights.iter())
+                .chain(layer.biases.iter_mut().zip(other.biases.iter()));
+
+            for (param, other_param) in params {
+                if rng.gen::<f64>() < 0.5 {
+                    *param = *other_param;
+                }
+
+                if rng.gen::<f64>() < self.mutation_rate {
+                    *param += rng.gen_range(-self.mutation_size, self.mutation_size);
+                }
+            }
+        }
+
+        child
+    }
+}
+
+impl Network for EvolvedNetwork {
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        self.nn.run(&board.to_binary())
+    }
+
+    fn play(&self, board: &Board) -> usize {
+        game::best_legal_move(board, &self.nn.run(&board.to_binary()))
+    }
+
+    // The current network is part of the first generation, so a warm
+    // start from learn isn't lost.
+    fn train(&mut self) {
+        assert!(self.elite < self.population);
+
+        let mut population: Vec<Individual> = (0..self.population)
+            .map(|i| {
+                let nn = if i == 0 { self.nn.clone() } else { EvolvedNetwork::new_nn() };
+                Individual { fitness: self.fitness(&nn), nn }
+            })
+            .collect();
+
+        for generation in 0..self.generations {
+            population.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+
+            let mean = population.iter().map(|i| i.fitness).sum::<f64>() / population.len() as f64;
+            println!("Generation {}: best fitness {:.3}, mean {:.3}",
+                generation + 1, population[0].fitness, mean);
+
+            let mut next: Vec<Individual> = population.drain(..self.elite).collect();
+
+            // The games are random, so the elite play again rather than
+            // keep a lucky fitness forever.
+            for individual in next.iter_mut() {
+                individual.fitness = self.fitness(&individual.nn);
+            }
+
+            while next.len() < self.population {
+                let nn = self.breed(&self.select(&population).nn, &self.select(&population).nn);
+                next.push(Individual { fitness: self.fitness(&nn), nn });
+            }
+
+            population = next;
+        }
+
+        population.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+        self.nn = population.swap_remove(0).nn;
+    }
+
+    // A supervised start for the evolution.
+    fn learn(&mut self, examples: &[Example]) {
+        for _ in 0..20 {
+            self.nn.train(examples, 32, 0.1);
+        }
+    }
+
+    fn test(&self) -> Metrics {
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+        println!("{}", metrics);
+        println!("Fitness: {:.3}", self.fitness(&self.nn));
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breed() {
+        let mut evolved = EvolvedNetwork::init();
+        let (parent1, parent2) = (EvolvedNetwork::new_nn(), EvolvedNetwork::new_nn());
+
+        // Without mutations every weight comes from one of the parents.
+        evolved.mutation_rate = 0.0;
+        let child = evolved.breed(&parent1, &parent2);
+
+        for l in 0..child.layers.len() {
+            for (i, w) in child.layers[l].weights.iter().enumerate() {
+                assert!(*w == parent1.layers[l].weights[i] || *w == parent2.layers[l].weights[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evolution() {
+        let mut evolved = EvolvedNetwork::init();
+        evolved.population = 4;
+        evolved.generations = 2;
+        evolved.games = 2;
+        evolved.elite = 1;
+        evolved.mcts_iterations = 10;
+
+        evolved.train();
+
+        let fitness = evolved.fitness(&evolved.nn);
+        assert!((0.0..=1.0).contains(&fitness));
+
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+        assert!(board.empty() & (1 << (evolved.play(&board) - 1)) != 0);
+    }
+}
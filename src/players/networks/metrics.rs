@@ -0,0 +1,177 @@
+use std::fmt;
+
+use crate::constants::*;
+
+use crate::board::Board;
+
+use crate::players::{
+    player::Player,
+    networks::network::Network,
+    alphabeta::AlphaBeta,
+    oracle::Oracle
+};
+
+// How a network does on a set of positions compared to perfect play.
+// Rates are between 0 and 1.
+pub struct Metrics {
+    pub positions: usize,
+
+    // AlphaBeta's move has the highest score, or one of the k highest.
+    pub top1: f64,
+    pub topk: f64,
+    pub k: usize,
+
+    // The move played is as good as AlphaBeta's, even if it's another one.
+    pub optimal: f64,
+
+    // The highest score is on an occupied square.
+    pub illegal: f64,
+
+    // Average points thrown away by the move played, where a win is 1 and
+    // a loss -1. Illegal moves count as losing the game.
+    pub score_loss: f64
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Positions: {}", self.positions)?;
+        writeln!(f, "Top-1 accuracy: {:.5} %", self.top1 * 100.0)?;
+        writeln!(f, "Top-{} accuracy: {:.5} %", self.k, self.topk * 100.0)?;
+        writeln!(f, "Optimal moves: {:.5} %", self.optimal * 100.0)?;
+        writeln!(f, "Illegal moves: {:.5} %", self.illegal * 100.0)?;
+        write!(f, "Average score loss: {:.5}", self.score_loss)
+    }
+}
+
+pub fn evaluate<T: Network + ?Sized>(net: &T, boards: &[Board], k: usize) -> Metrics {
+    assert!(!boards.is_empty());
+    assert!((1..=N2).contains(&k));
+
+    let ab = AlphaBeta{};
+
+    let mut top1 = 0;
+    let mut topk = 0;
+    let mut optimal = 0;
+    let mut illegal = 0;
+    let mut score_loss = 0;
+
+    for board in boards.iter() {
+        let scores = net.scores(board);
+        let best_move = ab.best_move(board);
+
+        // Squares from the highest score to the lowest, NaN last.
+        let score = |sqr: usize| if scores[sqr - 1].is_nan() { f64::NEG_INFINITY } else { scores[sqr - 1] };
+        let mut ranking: Vec<usize> = (1..N2+1).collect();
+        ranking.sort_by(|&a, &b| score(b).total_cmp(&score(a)));
+
+        if ranking[0] == best_move {
+            top1 += 1;
+        }
+
+        if ranking[..k].contains(&best_move) {
+            topk += 1;
+        }
+
+        if board.empty() & (1 << (ranking[0] - 1)) == 0 {
+            illegal += 1;
+        }
+
+        let value = Oracle::value(board);
+        let move_value = Oracle::move_value(board, net.play(board));
+
+        if move_value == value {
+            optimal += 1;
+        }
+
+        score_loss += value - move_value;
+    }
+
+    let n = boards.len() as f64;
+
+    Metrics {
+        positions: boards.len(),
+        top1: top1 as f64 / n,
+        topk: topk as f64 / n,
+        k,
+        optimal: optimal as f64 / n,
+        illegal: illegal as f64 / n,
+        score_loss: score_loss as f64 / n
+    }
+}
+
+// The share of the boards where the network plays an optimal move. Cheaper
+// than evaluate, for following the training as it goes.
+pub fn accuracy<T: Network + ?Sized>(net: &T, boards: &[Board]) -> f64 {
+    assert!(!boards.is_empty());
+
+    let optimal = boards.iter()
+        .filter(|board| Oracle::move_value(board, net.play(board)) == Oracle::value(board))
+        .count();
+
+    optimal as f64 / boards.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::networks::supervised::VALIDATION_BOARDS;
+
+    // Always plays the first empty square. The other squares score 0, or
+    // NaN like a network that has diverged.
+    struct FirstSquare {
+        nan: bool
+    }
+
+    impl Network for FirstSquare {
+        fn train(&mut self) {}
+        fn learn(&mut self, _: &[(Vec<f64>, Vec<f64>)]) {}
+
+        fn test(&self) -> Metrics {
+            evaluate(self, &VALIDATION_BOARDS, 3)
+        }
+
+        fn scores(&self, board: &Board) -> Vec<f64> {
+            let mut scores = vec![if self.nan { f64::NAN } else { 0.0 }; N2];
+            scores[self.play(board) - 1] = 1.0;
+            scores
+        }
+
+        fn play(&self, board: &Board) -> usize {
+            board.empty().trailing_zeros() as usize + 1
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let boards = [
+            // Winning at 3 is the only optimal move.
+            Board { tiles: [0b000000011, 0b000011000], turn: 0 },
+            // Square 1 wins as well as blocking.
+            Board { tiles: [0b000010010, 0b001001000], turn: 1 },
+            // Square 2 loses: 9 has to be blocked.
+            Board { tiles: [0b000010001, 0b000000100], turn: 1 }
+        ];
+
+        let metrics = evaluate(&FirstSquare{nan: false}, &boards, 3);
+
+        assert_eq!(metrics.positions, 3);
+        assert_eq!(metrics.illegal, 0.0);
+        assert!((metrics.optimal - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(accuracy(&FirstSquare{nan: false}, &boards), metrics.optimal);
+
+        // Giving away the game costs a draw's worth or more.
+        assert!(metrics.score_loss >= 1.0 / 3.0);
+
+        assert!(FirstSquare{nan: false}.test().positions > 0);
+    }
+
+    #[test]
+    fn test_nan_scores() {
+        let board = Board { tiles: [0b000000011, 0b000011000], turn: 0 };
+
+        // NaN ranks below every number instead of panicking.
+        let metrics = evaluate(&FirstSquare{nan: true}, &[board], 1);
+        assert_eq!(metrics.top1, 1.0);
+        assert_eq!(metrics.illegal, 0.0);
+    }
+}
@@ -1,2 +1,13 @@
 pub mod network;
-pub mod supervised;
\ No newline at end of file
+pub mod neural;
+pub mod metrics;
+pub mod training;
+pub mod tuning;
+pub mod dataset;
+pub mod supervised;
+pub mod conv;
+pub mod policyvalue;
+pub mod alphazero;
+pub mod evolution;
+pub mod reinforce;
+pub mod td;
\ No newline at end of file
@@ -1,15 +1,101 @@
+use crate::constants::*;
 use crate::board::Board;
+use crate::symmetries::{N_SYMMETRIES, transform_scores, inverse_symmetry};
 use crate::players::player::Player;
+use crate::players::networks::metrics::Metrics;
+use crate::players::networks::neural::Example;
 
 pub trait Network {
     fn train(&mut self);
-    fn test(&self);
 
+    // Trains on a given set of examples, such as a dataset loaded from
+    // disk, instead of the ones the network makes up itself.
+    fn learn(&mut self, examples: &[Example]);
+    fn test(&self) -> Metrics;
+
+    // How much the network likes every square, occupied or not.
+    fn scores(&self, board: &Board) -> Vec<f64>;
     fn play(&self, board: &Board) -> usize;
+
+    // The scores of the eight images of the board, moved back to the
+    // board's own squares and averaged.
+    fn symmetric_scores(&self, board: &Board) -> Vec<f64> {
+        let mut total = vec![0.0; N2];
+
+        for symmetry in 0..N_SYMMETRIES {
+            let scores = self.scores(&board.transform(symmetry));
+            let scores = transform_scores(&scores, inverse_symmetry(symmetry));
+
+            for i in 0..N2 {
+                total[i] += scores[i] / N_SYMMETRIES as f64;
+            }
+        }
+
+        total
+    }
 }
 
 impl<T> Player for T where T: Network {
     fn best_move(&self, board: &Board) -> usize {
         self.play(board)
     }
-}
\ No newline at end of file
+
+    fn move_scores(&self, board: &Board) -> Vec<f64> {
+        self.scores(board)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetries::transform_square;
+    use crate::players::networks::metrics;
+    use crate::players::networks::supervised::VALIDATION_BOARDS;
+
+    // Scores every square by what's on it, plus a skew towards the later
+    // squares that doesn't follow the symmetries.
+    struct Tiles {
+        skew: f64
+    }
+
+    impl Network for Tiles {
+        fn train(&mut self) {}
+        fn learn(&mut self, _: &[Example]) {}
+
+        fn test(&self) -> Metrics {
+            metrics::evaluate(self, &VALIDATION_BOARDS, 3)
+        }
+
+        fn scores(&self, board: &Board) -> Vec<f64> {
+            let planes = board.to_binary();
+            (0..N2).map(|i| planes[i] + 2.0 * planes[N2 + i] + self.skew * i as f64).collect()
+        }
+
+        fn play(&self, board: &Board) -> usize {
+            board.gen_moves()[0]
+        }
+    }
+
+    #[test]
+    fn test_symmetric_scores() {
+        // X on 1 and 2, O on 4: no symmetry keeps it as it is.
+        let board = Board { tiles: [0b000000011, 0b000001000], turn: 0 };
+
+        // The scores follow the tiles, so every image agrees.
+        let plain = Tiles { skew: 0.0 };
+        assert_eq!(plain.symmetric_scores(&board), plain.scores(&board));
+
+        // Each square gets the mean of where it lands in every image.
+        let skewed = Tiles { skew: 1.0 };
+        let averaged = skewed.symmetric_scores(&board);
+
+        for (sqr, &score) in averaged.iter().enumerate() {
+            let mean = (0..N_SYMMETRIES)
+                .map(|symmetry| skewed.scores(&board.transform(symmetry))[transform_square(sqr, symmetry)])
+                .sum::<f64>() / N_SYMMETRIES as f64;
+
+            assert!((score - mean).abs() < 1e-9);
+        }
+
+        assert!(averaged != skewed.scores(&board));
+    }
+}
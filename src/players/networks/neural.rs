@@ -0,0 +1,563 @@
+extern crate rand;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use std::io;
+
+// An input and the output it should produce.
+pub type Example = (Vec<f64>, Vec<f64>);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Activation {
+    Linear,
+    Sigmoid,
+    Tanh,
+    Relu,
+    Softmax
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Loss {
+    MeanSquared,
+    // Needs a softmax output for distributions or a sigmoid one for
+    // independent probabilities.
+    CrossEntropy
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Optimiser {
+    SGD { momentum: f64 },
+    Adam { beta1: f64, beta2: f64 }
+}
+
+// How the weights are drawn. Uniform draws them from (-x, x), like the
+// nn crate used to, while Xavier and He scale them with the layer's size
+// for sigmoid/tanh and relu layers respectively.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Init {
+    Uniform(f64),
+    Xavier,
+    He
+}
+
+#[derive(Clone)]
+pub struct Layer {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub activation: Activation,
+
+    // One row of weights per output.
+    pub weights: Vec<f64>,
+    pub biases: Vec<f64>,
+
+    // The optimiser's running averages of the gradients and, for Adam,
+    // of their squares.
+    moments: [Vec<f64>; 4]
+}
+
+// The gradients accumulated over a mini-batch.
+pub struct Gradients {
+    weights: Vec<Vec<f64>>,
+    biases: Vec<Vec<f64>>,
+    pub examples: usize
+}
+
+#[derive(Clone)]
+pub struct NN {
+    pub layers: Vec<Layer>,
+    pub loss: Loss,
+    pub optimiser: Optimiser,
+    steps: i32
+}
+
+impl Optimiser {
+    pub fn sgd() -> Optimiser {
+        Optimiser::SGD { momentum: 0.0 }
+    }
+
+    pub fn adam() -> Optimiser {
+        Optimiser::Adam { beta1: 0.9, beta2: 0.999 }
+    }
+}
+
+impl Activation {
+    pub fn apply(&self, z: &mut [f64]) {
+        match self {
+            Activation::Linear => (),
+            Activation::Sigmoid => for x in z.iter_mut() { *x = 1.0 / (1.0 + (-*x).exp()) },
+            Activation::Tanh => for x in z.iter_mut() { *x = x.tanh() },
+            Activation::Relu => for x in z.iter_mut() { *x = x.max(0.0) },
+            Activation::Softmax => {
+                let max = z.iter().cloned().fold(f64::MIN, f64::max);
+                let mut total = 0.0;
+
+                for x in z.iter_mut() {
+                    *x = (*x - max).exp();
+                    total += *x;
+                }
+
+                for x in z.iter_mut() {
+                    *x /= total;
+                }
+            }
+        }
+    }
+
+    // Takes the gradient with respect to the outputs back to the
+    // pre-activations, using only the outputs.
+    pub fn backward(&self, output: &[f64], grad: &[f64]) -> Vec<f64> {
+        match self {
+            Activation::Softmax => {
+                let dot: f64 = output.iter().zip(grad).map(|(a, g)| a * g).sum();
+                output.iter().zip(grad).map(|(a, g)| a * (g - dot)).collect()
+            },
+            _ => output.iter().zip(grad).map(|(&a, g)| g * self.derivative(a)).collect()
+        }
+    }
+
+    fn derivative(&self, output: f64) -> f64 {
+        match self {
+            Activation::Linear => 1.0,
+            Activation::Sigmoid => output * (1.0 - output),
+            Activation::Tanh => 1.0 - output * output,
+            Activation::Relu => if output > 0.0 { 1.0 } else { 0.0 },
+            Activation::Softmax => panic!("Softmax has no elementwise derivative.")
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Activation::Linear => "linear",
+            Activation::Sigmoid => "sigmoid",
+            Activation::Tanh => "tanh",
+            Activation::Relu => "relu",
+            Activation::Softmax => "softmax"
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Activation> {
+        match name {
+            "linear" => Some(Activation::Linear),
+            "sigmoid" => Some(Activation::Sigmoid),
+            "tanh" => Some(Activation::Tanh),
+            "relu" => Some(Activation::Relu),
+            "softmax" => Some(Activation::Softmax),
+            _ => None
+        }
+    }
+}
+
+impl Loss {
+    // Takes the output activation like delta, as a sigmoid output is a set
+    // of independent probabilities and also pays for the wrong negatives.
+    pub fn value(&self, activation: Activation, output: &[f64], target: &[f64]) -> f64 {
+        const EPSILON: f64 = 1e-12;
+
+        match (self, activation) {
+            (Loss::MeanSquared, _) => {
+                output.iter().zip(target).map(|(o, t)| (o - t) * (o - t)).sum::<f64>() / 2.0
+            },
+            (Loss::CrossEntropy, Activation::Sigmoid) => {
+                -output.iter().zip(target)
+                    .map(|(o, t)| t * o.max(EPSILON).ln() + (1.0 - t) * (1.0 - o).max(EPSILON).ln())
+                    .sum::<f64>()
+            },
+            (Loss::CrossEntropy, _) => {
+                -output.iter().zip(target).map(|(o, t)| t * o.max(EPSILON).ln()).sum::<f64>()
+            }
+        }
+    }
+
+    // The gradient with respect to the output layer's pre-activations.
+    pub fn delta(&self, activation: Activation, output: &[f64], target: &[f64]) -> Vec<f64> {
+        let difference: Vec<f64> = output.iter().zip(target).map(|(o, t)| o - t).collect();
+
+        match (self, activation) {
+            // Both simplify to the difference with the target.
+            (Loss::CrossEntropy, Activation::Softmax) => difference,
+            (Loss::CrossEntropy, Activation::Sigmoid) => difference,
+            (Loss::CrossEntropy, _) => panic!("Cross-entropy needs a softmax or sigmoid output."),
+            (Loss::MeanSquared, _) => activation.backward(output, &difference)
+        }
+    }
+}
+
+impl Layer {
+    fn new(inputs: usize, outputs: usize, activation: Activation, init: Init) -> Layer {
+        let mut rng = rand::thread_rng();
+
+        let limit = match init {
+            Init::Uniform(limit) => limit,
+            Init::Xavier => (6.0 / (inputs + outputs) as f64).sqrt(),
+            Init::He => (6.0 / inputs as f64).sqrt()
+        };
+
+        let weights = (0..inputs * outputs).map(|_| rng.gen_range(-limit, limit)).collect();
+
+        let biases = match init {
+            Init::Uniform(limit) => (0..outputs).map(|_| rng.gen_range(-limit, limit)).collect(),
+            _ => vec![0.0; outputs]
+        };
+
+        Layer::from_parameters(inputs, outputs, activation, weights, biases)
+    }
+
+    fn from_parameters(inputs: usize, outputs: usize, activation: Activation,
+                       weights: Vec<f64>, biases: Vec<f64>) -> Layer {
+        let moments = [
+            vec![0.0; inputs * outputs], vec![0.0; inputs * outputs],
+            vec![0.0; outputs], vec![0.0; outputs]
+        ];
+
+        Layer { inputs, outputs, activation, weights, biases, moments }
+    }
+
+    fn forward(&self, input: &[f64]) -> Vec<f64> {
+        assert_eq!(input.len(), self.inputs);
+
+        let mut output = self.biases.clone();
+
+        for (out, row) in output.iter_mut().zip(self.weights.chunks(self.inputs)) {
+            *out += row.iter().zip(input).map(|(w, x)| w * x).sum::<f64>();
+        }
+
+        self.activation.apply(&mut output);
+        output
+    }
+}
+
+impl NN {
+    // The first size is the input, the last one the output and the ones
+    // in between are hidden layers, which share an activation.
+    pub fn new(sizes: &[usize], hidden: Activation, output: Activation, init: Init) -> NN {
+        assert!(sizes.len() >= 2, "A network needs at least two layers.");
+        assert!(sizes.iter().all(|&size| size > 0), "A network can't have empty layers.");
+
+        let mut layers = Vec::with_capacity(sizes.len() - 1);
+
+        for i in 1..sizes.len() {
+            let activation = if i == sizes.len() - 1 { output } else { hidden };
+            layers.push(Layer::new(sizes[i - 1], sizes[i], activation, init));
+        }
+
+        NN { layers, loss: Loss::MeanSquared, optimiser: Optimiser::sgd(), steps: 0 }
+    }
+
+    // Returns the input followed by the output of every layer.
+    pub fn forward(&self, input: &[f64]) -> Vec<Vec<f64>> {
+        let mut activations = vec![input.to_vec()];
+
+        for layer in self.layers.iter() {
+            let output = layer.forward(activations.last().unwrap());
+            activations.push(output);
+        }
+
+        activations
+    }
+
+    pub fn run(&self, input: &[f64]) -> Vec<f64> {
+        self.forward(input).pop().unwrap()
+    }
+
+    pub fn gradients(&self) -> Gradients {
+        Gradients {
+            weights: self.layers.iter().map(|l| vec![0.0; l.weights.len()]).collect(),
+            biases: self.layers.iter().map(|l| vec![0.0; l.biases.len()]).collect(),
+            examples: 0
+        }
+    }
+
+    // The gradient with respect to the output layer's pre-activations
+    // given the one with respect to its outputs. Useful when the network
+    // feeds into others instead of a loss.
+    pub fn output_delta(&self, activations: &[Vec<f64>], grad: &[f64]) -> Vec<f64> {
+        self.layers.last().unwrap().activation.backward(activations.last().unwrap(), grad)
+    }
+
+    // Adds the gradients of one example, given the activations from
+    // forward and the output layer's delta. Returns the gradient with
+    // respect to the input.
+    pub fn backward(&self, activations: &[Vec<f64>], delta: Vec<f64>, gradients: &mut Gradients) -> Vec<f64> {
+        let mut delta = delta;
+
+        for l in (0..self.layers.len()).rev() {
+            let layer = &self.layers[l];
+            let input = &activations[l];
+
+            let mut grad_input = vec![0.0; layer.inputs];
+
+            for (o, &d) in delta.iter().enumerate() {
+                gradients.biases[l][o] += d;
+
+                for i in 0..layer.inputs {
+                    gradients.weights[l][o * layer.inputs + i] += d * input[i];
+                    grad_input[i] += d * layer.weights[o * layer.inputs + i];
+                }
+            }
+
+            delta = if l > 0 {
+                self.layers[l - 1].activation.backward(input, &grad_input)
+            } else {
+                grad_input
+            };
+        }
+
+        delta
+    }
+
+    // Updates the weights with the mean of the accumulated gradients.
+    pub fn step(&mut self, gradients: &Gradients, rate: f64) {
+        if gradients.examples == 0 {
+            return;
+        }
+
+        self.steps += 1;
+
+        let scale = 1.0 / gradients.examples as f64;
+        let optimiser = self.optimiser;
+        let steps = self.steps;
+
+        for (l, layer) in self.layers.iter_mut().enumerate() {
+            let (weight_moments, bias_moments) = layer.moments.split_at_mut(2);
+
+            update(&mut layer.weights, &gradients.weights[l], weight_moments, optimiser, rate, scale, steps);
+            update(&mut layer.biases, &gradients.biases[l], bias_moments, optimiser, rate, scale, steps);
+        }
+    }
+
+    // One pass over the examples in shuffled mini-batches.
+    // Returns the mean loss.
+    pub fn train(&mut self, examples: &[Example], batch_size: usize, rate: f64) -> f64 {
+        assert!(batch_size > 0);
+
+        let mut order: Vec<usize> = (0..examples.len()).collect();
+        order.shuffle(&mut rand::thread_rng());
+
+        let activation = self.layers.last().unwrap().activation;
+        let mut total_loss = 0.0;
+
+        for batch in order.chunks(batch_size) {
+            let mut gradients = self.gradients();
+
+            for &i in batch {
+                let (input, target) = &examples[i];
+                let activations = self.forward(input);
+                let output = activations.last().unwrap();
+
+                total_loss += self.loss.value(activation, output, target);
+
+                let delta = self.loss.delta(activation, output, target);
+                self.backward(&activations, delta, &mut gradients);
+                gradients.examples += 1;
+            }
+
+            self.step(&gradients, rate);
+        }
+
+        total_loss / examples.len() as f64
+    }
+
+    // A plain-text format: the loss and optimiser, then every layer's
+    // shape and activation followed by its weights and biases.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        let loss = match self.loss {
+            Loss::MeanSquared => "mse",
+            Loss::CrossEntropy => "cross-entropy"
+        };
+
+        text.push_str(&format!("loss {}\n", loss));
+
+        match self.optimiser {
+            Optimiser::SGD { momentum } => text.push_str(&format!("sgd {}\n", momentum)),
+            Optimiser::Adam { beta1, beta2 } => text.push_str(&format!("adam {} {}\n", beta1, beta2))
+        }
+
+        for layer in self.layers.iter() {
+            let weights: Vec<String> = layer.weights.iter().map(|w| w.to_string()).collect();
+            let biases: Vec<String> = layer.biases.iter().map(|b| b.to_string()).collect();
+
+            text.push_str(&format!("layer {} {} {}\n", layer.inputs, layer.outputs, layer.activation.name()));
+            text.push_str(&format!("{}\n{}\n", weights.join(" "), biases.join(" ")));
+        }
+
+        text
+    }
+
+    pub fn from_text(text: &str) -> io::Result<NN> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Malformed network.");
+
+        let mut lines = text.lines();
+        let mut next_fields = || -> io::Result<Vec<&str>> {
+            Ok(lines.next().ok_or_else(invalid)?.split_whitespace().collect())
+        };
+
+        let loss = match next_fields()?.as_slice() {
+            ["loss", "mse"] => Loss::MeanSquared,
+            ["loss", "cross-entropy"] => Loss::CrossEntropy,
+            _ => return Err(invalid())
+        };
+
+        let optimiser = match next_fields()?.as_slice() {
+            ["sgd", momentum] => Optimiser::SGD { momentum: momentum.parse().map_err(|_| invalid())? },
+            ["adam", beta1, beta2] => Optimiser::Adam {
+                beta1: beta1.parse().map_err(|_| invalid())?,
+                beta2: beta2.parse().map_err(|_| invalid())?
+            },
+            _ => return Err(invalid())
+        };
+
+        let mut layers = Vec::new();
+
+        while let Ok(header) = next_fields() {
+
+            if header.len() != 4 || header[0] != "layer" {
+                return Err(invalid());
+            }
+
+            let inputs: usize = header[1].parse().map_err(|_| invalid())?;
+            let outputs: usize = header[2].parse().map_err(|_| invalid())?;
+            let activation = Activation::from_name(header[3]).ok_or_else(invalid)?;
+
+            let parse = |fields: Vec<&str>, len: usize| -> io::Result<Vec<f64>> {
+                let values: Result<Vec<f64>, _> = fields.iter().map(|f| f.parse()).collect();
+                let values = values.map_err(|_| invalid())?;
+
+                if values.len() == len { Ok(values) } else { Err(invalid()) }
+            };
+
+            let weights = parse(next_fields()?, inputs * outputs)?;
+            let biases = parse(next_fields()?, outputs)?;
+
+            layers.push(Layer::from_parameters(inputs, outputs, activation, weights, biases));
+        }
+
+        if layers.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(NN { layers, loss, optimiser, steps: 0 })
+    }
+}
+
+// Moves the parameters against the gradients, which are multiplied by
+// scale, keeping the optimiser's state in the two moments.
+pub fn update(params: &mut [f64], grads: &[f64], moments: &mut [Vec<f64>],
+          optimiser: Optimiser, rate: f64, scale: f64, steps: i32) {
+    match optimiser {
+        Optimiser::SGD { momentum } => {
+            let velocity = &mut moments[0];
+
+            for i in 0..params.len() {
+                velocity[i] = momentum * velocity[i] - rate * grads[i] * scale;
+                params[i] += velocity[i];
+            }
+        },
+        Optimiser::Adam { beta1, beta2 } => {
+            const EPSILON: f64 = 1e-8;
+
+            let correction1 = 1.0 - beta1.powi(steps);
+            let correction2 = 1.0 - beta2.powi(steps);
+
+            let (first, second) = moments.split_at_mut(1);
+            let (m, v) = (&mut first[0], &mut second[0]);
+
+            for i in 0..params.len() {
+                let g = grads[i] * scale;
+
+                m[i] = beta1 * m[i] + (1.0 - beta1) * g;
+                v[i] = beta2 * v[i] + (1.0 - beta2) * g * g;
+
+                params[i] -= rate * (m[i] / correction1) / ((v[i] / correction2).sqrt() + EPSILON);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compares the backpropagated gradients with finite differences.
+    fn check_gradients(mut nn: NN, input: &[f64], target: &[f64]) {
+        const H: f64 = 1e-6;
+
+        let activation = nn.layers.last().unwrap().activation;
+        let activations = nn.forward(input);
+        let delta = nn.loss.delta(activation, activations.last().unwrap(), target);
+
+        let mut gradients = nn.gradients();
+        nn.backward(&activations, delta, &mut gradients);
+
+        for l in 0..nn.layers.len() {
+            for i in 0..nn.layers[l].weights.len() {
+                let original = nn.layers[l].weights[i];
+
+                nn.layers[l].weights[i] = original + H;
+                let plus = nn.loss.value(activation, &nn.run(input), target);
+                nn.layers[l].weights[i] = original - H;
+                let minus = nn.loss.value(activation, &nn.run(input), target);
+                nn.layers[l].weights[i] = original;
+
+                let numerical = (plus - minus) / (2.0 * H);
+                assert!((numerical - gradients.weights[l][i]).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradients() {
+        let input = [0.5, -1.0, 0.25];
+
+        let mut nn = NN::new(&[3, 5, 4], Activation::Tanh, Activation::Softmax, Init::Xavier);
+        nn.loss = Loss::CrossEntropy;
+        check_gradients(nn, &input, &[0.0, 1.0, 0.0, 0.0]);
+
+        let nn = NN::new(&[3, 5, 4, 2], Activation::Sigmoid, Activation::Softmax, Init::Uniform(0.5));
+        check_gradients(nn, &input, &[0.3, 0.7]);
+
+        let nn = NN::new(&[3, 6, 1], Activation::Relu, Activation::Tanh, Init::He);
+        check_gradients(nn, &input, &[-0.5]);
+
+        let mut nn = NN::new(&[3, 5, 3], Activation::Tanh, Activation::Sigmoid, Init::Xavier);
+        nn.loss = Loss::CrossEntropy;
+        check_gradients(nn, &input, &[1.0, 0.0, 0.4]);
+    }
+
+    #[test]
+    fn test_learns_xor() {
+        let examples: Vec<Example> = vec![
+            (vec![0.0, 0.0], vec![0.0]),
+            (vec![0.0, 1.0], vec![1.0]),
+            (vec![1.0, 0.0], vec![1.0]),
+            (vec![1.0, 1.0], vec![0.0])
+        ];
+
+        let mut nn = NN::new(&[2, 8, 1], Activation::Tanh, Activation::Sigmoid, Init::Xavier);
+        nn.loss = Loss::CrossEntropy;
+        nn.optimiser = Optimiser::adam();
+
+        for _ in 0..2000 {
+            nn.train(&examples, 2, 0.05);
+        }
+
+        for (input, target) in examples.iter() {
+            assert!((nn.run(input)[0] - target[0]).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut nn = NN::new(&[4, 3, 2], Activation::Relu, Activation::Softmax, Init::He);
+        nn.loss = Loss::CrossEntropy;
+        nn.optimiser = Optimiser::adam();
+
+        let loaded = NN::from_text(&nn.to_text()).unwrap();
+        let input = [0.1, 0.2, 0.3, 0.4];
+
+        assert_eq!(loaded.run(&input), nn.run(&input));
+        assert_eq!(loaded.loss, nn.loss);
+        assert_eq!(loaded.optimiser, nn.optimiser);
+    }
+}
@@ -0,0 +1,337 @@
+extern crate rand;
+use rand::seq::SliceRandom;
+
+use std::fs;
+use std::io;
+
+use crate::constants::*;
+use crate::game;
+
+use crate::board::Board;
+
+use crate::players::{
+    evaluator::Evaluator,
+    networks::network::Network,
+    networks::neural::{NN, Activation, Loss, Optimiser, Init},
+    networks::metrics::{self, Metrics},
+    networks::training::{Schedule, TrainingLog, Record},
+    networks::supervised::{self, VALIDATION_BOARDS},
+    networks::dataset::Label,
+    alphabeta::AlphaBeta
+};
+
+// An input and its target: the policy over every square followed by
+// the value of the position, between -1 and 1.
+pub type Example = (Vec<f64>, Vec<f64>);
+
+// A shared trunk with two heads on top: a softmax over the squares
+// and a single tanh output for the value of the position.
+#[derive(Clone)]
+pub struct PolicyValueNetwork {
+    pub trunk: NN,
+    pub policy: NN,
+    pub value: NN,
+    pub augment: bool,
+    pub average_symmetries: bool,
+    pub rounds: usize,
+    pub epochs: u32,
+    pub batch_size: usize,
+    pub learning_rate: f64,
+    pub schedule: Schedule,
+    pub log: TrainingLog
+}
+
+impl PolicyValueNetwork {
+    pub fn init() -> PolicyValueNetwork {
+        let mut trunk = NN::new(&[27, 81, 27], Activation::Relu, Activation::Relu, Init::He);
+        let mut policy = NN::new(&[27, N2], Activation::Linear, Activation::Softmax, Init::Xavier);
+        let mut value = NN::new(&[27, 1], Activation::Linear, Activation::Tanh, Init::Xavier);
+
+        policy.loss = Loss::CrossEntropy;
+
+        for nn in [&mut trunk, &mut policy, &mut value].iter_mut() {
+            nn.optimiser = Optimiser::adam();
+        }
+
+        PolicyValueNetwork {
+            trunk,
+            policy,
+            value,
+            augment: false,
+            average_symmetries: false,
+            rounds: 100,
+            epochs: 1,
+            batch_size: 32,
+            learning_rate: 0.005,
+            schedule: Schedule::Exponential(0.97),
+            log: TrainingLog::init()
+        }
+    }
+
+    // Returns the move distribution, restricted to the empty squares,
+    // and the value of the position for the side to move.
+    pub fn run(&self, board: &Board) -> ([f64; N2], f64) {
+        let features = self.trunk.run(&board.to_binary());
+        let output = self.policy.run(&features);
+        let empty = board.empty();
+
+        let mut policy: [f64; N2] = [0.0; N2];
+        let mut total = 0.0;
+
+        for i in 0..N2 {
+            if empty & (1 << i) != 0 {
+                policy[i] = output[i];
+                total += output[i];
+            }
+        }
+
+        for p in policy.iter_mut() {
+            *p /= total;
+        }
+
+        (policy, self.value.run(&features)[0])
+    }
+
+    pub fn target(policy: &[f64; N2], value: f64) -> Vec<f64> {
+        let mut target = policy.to_vec();
+        target.push(value);
+        target
+    }
+
+    // Trains both heads and the trunk they share, which receives the sum
+    // of their gradients. Returns the mean loss of the last epoch.
+    pub fn fit(&mut self, examples: &[Example]) -> f64 {
+        let mut order: Vec<usize> = (0..examples.len()).collect();
+        let mut total_loss = 0.0;
+
+        for _ in 0..self.epochs {
+            order.shuffle(&mut rand::thread_rng());
+            total_loss = 0.0;
+
+            for batch in order.chunks(self.batch_size) {
+                let mut trunk_gradients = self.trunk.gradients();
+                let mut policy_gradients = self.policy.gradients();
+                let mut value_gradients = self.value.gradients();
+
+                for &i in batch {
+                    let (input, target) = &examples[i];
+
+                    let trunk_activations = self.trunk.forward(input);
+                    let features = trunk_activations.last().unwrap();
+
+                    let policy_activations = self.policy.forward(features);
+                    let value_activations = self.value.forward(features);
+
+                    let policy = policy_activations.last().unwrap();
+                    let value = value_activations.last().unwrap();
+
+                    total_loss += self.policy.loss.value(Activation::Softmax, policy, &target[..N2]);
+                    total_loss += self.value.loss.value(Activation::Tanh, value, &target[N2..]);
+
+                    let policy_delta = self.policy.loss.delta(Activation::Softmax, policy, &target[..N2]);
+                    let value_delta = self.value.loss.delta(Activation::Tanh, value, &target[N2..]);
+
+                    let mut grad = self.policy.backward(&policy_activations, policy_delta, &mut policy_gradients);
+                    let value_grad = self.value.backward(&value_activations, value_delta, &mut value_gradients);
+
+                    for (g, v) in grad.iter_mut().zip(value_grad) {
+                        *g += v;
+                    }
+
+                    let trunk_delta = self.trunk.output_delta(&trunk_activations, &grad);
+                    self.trunk.backward(&trunk_activations, trunk_delta, &mut trunk_gradients);
+
+                    trunk_gradients.examples += 1;
+                    policy_gradients.examples += 1;
+                    value_gradients.examples += 1;
+                }
+
+                self.trunk.step(&trunk_gradients, self.learning_rate);
+                self.policy.step(&policy_gradients, self.learning_rate);
+                self.value.step(&value_gradients, self.learning_rate);
+            }
+        }
+
+        total_loss / examples.len() as f64
+    }
+
+    // The three networks one after the other, separated by blank lines.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let texts = [self.trunk.to_text(), self.policy.to_text(), self.value.to_text()];
+        fs::write(path, texts.join("\n"))
+    }
+
+    pub fn load(&mut self, path: &str) -> io::Result<()> {
+        let text = fs::read_to_string(path)?;
+        let sections: Vec<&str> = text.split("\n\n").collect();
+
+        if sections.len() != 3 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Malformed network."));
+        }
+
+        self.trunk = NN::from_text(sections[0])?;
+        self.policy = NN::from_text(sections[1])?;
+        self.value = NN::from_text(sections[2])?;
+
+        Ok(())
+    }
+}
+
+impl Evaluator for PolicyValueNetwork {
+    fn evaluate(&self, board: &Board) -> ([f64; N2], f64) {
+        self.run(board)
+    }
+}
+
+impl Network for PolicyValueNetwork {
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        let features = self.trunk.run(&board.to_binary());
+        self.policy.run(&features)
+    }
+
+    fn play(&self, board: &Board) -> usize {
+        let policy = if self.average_symmetries {
+            self.symmetric_scores(board)
+        } else {
+            self.run(board).0.to_vec()
+        };
+
+        game::best_legal_move(board, &policy)
+    }
+
+    // The policy imitates AlphaBeta's move and the value its score.
+    fn train(&mut self) {
+        let data = supervised::training_data(self.augment, |board| Label::PolicyValue.target(board));
+        self.learn(&data);
+    }
+
+    // The targets need the value after the policy.
+    fn learn(&mut self, data: &[Example]) {
+        let rate = self.learning_rate;
+        self.log.clear();
+
+        // fit reads the rate from the network, so it's swapped for the
+        // scheduled one while training.
+        for round in 0..self.rounds {
+            self.learning_rate = self.schedule.rate(rate, round, self.rounds);
+            let loss = self.fit(data);
+
+            if self.log.active() {
+                let accuracy = metrics::accuracy(self, &VALIDATION_BOARDS);
+                let epoch = (round + 1) * self.epochs as usize;
+
+                self.log.record(Record { epoch, loss, accuracy, learning_rate: self.learning_rate })
+                    .expect("Couldn't write the training log.");
+            }
+        }
+
+        self.learning_rate = rate;
+    }
+
+    // Checks the policy against perfect play and how often the value
+    // rounds to AlphaBeta's score, on the boards held out from training.
+    fn test(&self) -> Metrics {
+        let mut correct_values = 0.0;
+
+        for board in VALIDATION_BOARDS.iter() {
+            let (score, _) = AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE);
+            let (_, value) = self.run(board);
+
+            if value.round() as isize == score.signum() {
+                correct_values += 1.0;
+            }
+        }
+
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+
+        println!("{}", metrics);
+        println!("Correct value: {:.5} %", correct_values * 100.0 / VALIDATION_BOARDS.len() as f64);
+
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(net: &mut PolicyValueNetwork, n: usize) -> &mut NN {
+        match n {
+            0 => &mut net.trunk,
+            1 => &mut net.policy,
+            _ => &mut net.value
+        }
+    }
+
+    // A single plain SGD step of fit at rate 1 moves every weight by its
+    // gradient, which is compared with finite differences of the summed
+    // losses of both heads. The trunk's come through both of them.
+    #[test]
+    fn test_gradients() {
+        const H: f64 = 1e-6;
+
+        let mut net = PolicyValueNetwork::init();
+        net.learning_rate = 1.0;
+        net.batch_size = 1;
+
+        for n in 0..3 {
+            part(&mut net, n).optimiser = Optimiser::sgd();
+        }
+
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+        let input = board.to_binary().to_vec();
+
+        let mut policy = [0.0; N2];
+        policy[3] = 0.75;
+        policy[6] = 0.25;
+        let target = PolicyValueNetwork::target(&policy, 0.5);
+
+        let loss = |net: &PolicyValueNetwork| {
+            let features = net.trunk.run(&input);
+
+            net.policy.loss.value(Activation::Softmax, &net.policy.run(&features), &target[..N2])
+                + net.value.loss.value(Activation::Tanh, &net.value.run(&features), &target[N2..])
+        };
+
+        let mut stepped = net.clone();
+        stepped.fit(&[(input.clone(), target.clone())]);
+
+        for n in 0..3 {
+            for l in 0..part(&mut net, n).layers.len() {
+                for i in (0..part(&mut net, n).layers[l].weights.len()).step_by(7) {
+                    let original = part(&mut net, n).layers[l].weights[i];
+                    let gradient = original - part(&mut stepped, n).layers[l].weights[i];
+
+                    part(&mut net, n).layers[l].weights[i] = original + H;
+                    let plus = loss(&net);
+                    part(&mut net, n).layers[l].weights[i] = original - H;
+                    let minus = loss(&net);
+                    part(&mut net, n).layers[l].weights[i] = original;
+
+                    assert!(((plus - minus) / (2.0 * H) - gradient).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join("policyvalue_test.txt");
+        let path = path.to_str().unwrap();
+
+        let net = PolicyValueNetwork::init();
+        net.save(path).unwrap();
+
+        let mut loaded = PolicyValueNetwork::init();
+        loaded.load(path).unwrap();
+
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+        assert_eq!(loaded.run(&board), net.run(&board));
+
+        // Each network is a section of its own.
+        fs::write(path, net.trunk.to_text()).unwrap();
+        assert!(loaded.load(path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+extern crate rand;
+use rand::Rng;
+
+use crate::constants::*;
+use crate::game::{self, Progress};
+
+use crate::board::{Board, State};
+
+use crate::players::{
+    networks::network::Network,
+    networks::neural::{NN, Activation, Loss, Optimiser, Init, Example},
+    networks::metrics::{self, Metrics},
+    networks::supervised::VALIDATION_BOARDS,
+    alphabeta::AlphaBeta,
+    random::RandomPlayer
+};
+
+// REINFORCE: the network plays itself, sampling its moves from the softmax
+// over the empty squares, and every move is made more likely the better
+// the game went for the side that played it compared to a baseline.
+pub struct PolicyGradient {
+    pub nn: NN,
+    pub games: usize,
+
+    // Games played between updates.
+    pub batch_size: usize,
+    pub learning_rate: f64,
+
+    // How fast the baseline follows the returns.
+    pub baseline_rate: f64,
+
+    // Weight of the policy's entropy in the loss, which keeps it from
+    // settling on a single game against itself too soon.
+    pub entropy: f64,
+
+    pub eval_every: usize,
+    pub curve: Vec<Progress>,
+
+    // The average return of the moves made at every ply.
+    baseline: [f64; N2]
+}
+
+// A move of a self-play game and the return of the side that made it.
+struct Step {
+    input: Vec<f64>,
+    policy: [f64; N2],
+    move_: usize,
+    ply: usize,
+    reward: f64
+}
+
+impl PolicyGradient {
+    pub fn init() -> PolicyGradient {
+        let mut nn = NN::new(&[27, 81, N2], Activation::Relu, Activation::Softmax, Init::He);
+        nn.loss = Loss::CrossEntropy;
+        nn.optimiser = Optimiser::adam();
+
+        PolicyGradient {
+            nn,
+            games: 50000,
+            batch_size: 8,
+            learning_rate: 0.002,
+            baseline_rate: 0.01,
+            entropy: 0.1,
+            eval_every: 5000,
+            curve: Vec::new(),
+            baseline: [0.0; N2]
+        }
+    }
+
+    // The network's distribution restricted to the empty squares.
+    pub fn policy(&self, board: &Board) -> [f64; N2] {
+        let output = self.nn.run(&board.to_binary());
+        let empty = board.empty();
+
+        let mut policy: [f64; N2] = [0.0; N2];
+
+        for i in 0..N2 {
+            if empty & (1 << i) != 0 {
+                policy[i] = output[i];
+            }
+        }
+
+        let total: f64 = policy.iter().sum();
+
+        for p in policy.iter_mut() {
+            *p /= total;
+        }
+
+        policy
+    }
+
+    fn self_play(&self) -> Vec<Step> {
+        let mut board = Board { tiles: [0, 0], turn: rand::thread_rng().gen_range(0, 2) };
+        let mut history = Vec::new();
+
+        while board.state() == State::Unfinished {
+            let policy = self.policy(&board);
+            let move_ = game::sample_move(&policy);
+
+            history.push((board.to_binary().to_vec(), policy, move_, board.turn));
+            board.make(move_);
+        }
+
+        let state = board.state();
+
+        history.into_iter().enumerate().map(|(ply, (input, policy, move_, turn))| {
+            // The last player to move is the one who won.
+            let reward = if state == State::Draw {
+                0.0
+            } else if turn != board.turn {
+                1.0
+            } else {
+                -1.0
+            };
+
+            Step { input, policy, move_, ply, reward }
+        }).collect()
+    }
+
+    // A gradient step on the log-probability of every move played,
+    // weighed by its advantage over the baseline.
+    fn reinforce(&mut self, steps: &[Step]) {
+        let mut gradients = self.nn.gradients();
+
+        for step in steps.iter() {
+            let advantage = step.reward - self.baseline[step.ply];
+            self.baseline[step.ply] += self.baseline_rate * advantage;
+
+            // The loss is -advantage * log(policy[move_]) - entropy * H, and
+            // the squares that are taken don't take part in the softmax.
+            let h: f64 = step.policy.iter().filter(|&&p| p > 0.0).map(|p| -p * p.ln()).sum();
+            let mut delta = vec![0.0; N2];
+
+            for (i, &p) in step.policy.iter().enumerate() {
+                if p > 0.0 {
+                    let played = if i + 1 == step.move_ { 1.0 } else { 0.0 };
+                    delta[i] = -advantage * (played - p) + self.entropy * p * (p.ln() + h);
+                }
+            }
+
+            let activations = self.nn.forward(&step.input);
+            self.nn.backward(&activations, delta, &mut gradients);
+            gradients.examples += 1;
+        }
+
+        self.nn.step(&gradients, self.learning_rate);
+    }
+
+    fn evaluate(&self, games: usize) -> Progress {
+        Progress::measure(self, games, &[("random", &RandomPlayer), ("alphabeta", &AlphaBeta{})])
+    }
+}
+
+impl Network for PolicyGradient {
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        self.nn.run(&board.to_binary())
+    }
+
+    // Greedy once trained.
+    fn play(&self, board: &Board) -> usize {
+        game::best_legal_move(board, &self.policy(board))
+    }
+
+    fn train(&mut self) {
+        let mut steps = Vec::new();
+
+        for game in 1..self.games+1 {
+            steps.extend(self.self_play());
+
+            if game % self.batch_size == 0 {
+                self.reinforce(&steps);
+                steps.clear();
+            }
+
+            if game % self.eval_every == 0 {
+                let progress = self.evaluate(game);
+                self.curve.push(progress);
+            }
+        }
+    }
+
+    // Imitation before the self-play, so it doesn't start from random.
+    fn learn(&mut self, examples: &[Example]) {
+        for _ in 0..20 {
+            self.nn.train(examples, 32, self.learning_rate);
+        }
+    }
+
+    fn test(&self) -> Metrics {
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+        println!("{}", metrics);
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        let agent = PolicyGradient::init();
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+        let policy = agent.policy(&board);
+
+        assert!((policy.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        for (i, &p) in policy.iter().enumerate() {
+            assert_eq!(p > 0.0, board.empty() & (1 << i) != 0);
+        }
+
+        for step in agent.self_play() {
+            assert!(step.reward.abs() <= 1.0);
+            assert!(step.policy[step.move_ - 1] > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_reinforce() {
+        let mut agent = PolicyGradient::init();
+        agent.learning_rate = 0.01;
+
+        // Rewarding the winning move makes it more likely.
+        let board = Board { tiles: [0b000000011, 0b000011000], turn: 0 };
+        let before = agent.policy(&board)[2];
+
+        for _ in 0..20 {
+            let step = Step {
+                input: board.to_binary().to_vec(),
+                policy: agent.policy(&board),
+                move_: 3,
+                ply: 4,
+                reward: 1.0
+            };
+
+            agent.reinforce(&[step]);
+        }
+
+        assert!(agent.policy(&board)[2] > before);
+    }
+}
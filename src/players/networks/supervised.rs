@@ -1,112 +1,200 @@
-use nn::{NN, HaltCondition};
+
+extern crate rand;
+use rand::{SeedableRng, rngs::StdRng};
+use rand::seq::SliceRandom;
+
+use std::collections::HashSet;
 
 use crate::constants::*;
 
 use crate::board;
 use crate::board::{Board, State};
+use crate::symmetries;
 
 use crate::players::{
-    player::Player, 
     networks::network::Network,
-    alphabeta::AlphaBeta    
+    networks::neural::{NN, Activation, Loss, Init, Example},
+    networks::dataset::Label,
+    networks::metrics::{self, Metrics},
+    networks::training::{Schedule, TrainingLog, Record}
 };
 
+// Share of the boards held out to validate the networks.
+pub const VALIDATION_SHARE: f64 = 0.2;
+
 lazy_static! {
-    static ref ALL_BOARDS: Vec<Board> = {
+    pub static ref ALL_BOARDS: Vec<Board> = {
         let mut v = Vec::new();
         leaf_boards(Board{tiles: [0, 0], turn: 0}, &mut v);
         v
     };
+
+    // The split is always the same so that networks can be compared, and
+    // all the images of a position fall on the same side of it.
+    pub static ref TRAINING_BOARDS: Vec<Board> = split_boards().0;
+    pub static ref VALIDATION_BOARDS: Vec<Board> = split_boards().1;
+}
+
+// What the network learns to play on every board.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Targets {
+    // The move AlphaBeta finds, even when others are as good.
+    BestMove,
+    // An even share on every optimal move, as given by the Oracle.
+    Optimal
 }
 
 pub struct SupervisedNetwork {
     pub nn: NN,
+    pub targets: Targets,
+    pub augment: bool,
+    pub average_symmetries: bool,
+    pub rounds: usize,
     pub epochs: u32,
+    pub batch_size: usize,
     pub learning_rate: f64,
+    pub schedule: Schedule,
+    pub log: TrainingLog
 }
 
 impl SupervisedNetwork {
     pub fn init() -> SupervisedNetwork {
-        let size = [27, 81, 27, 9];
+        SupervisedNetwork::with_hidden(&[81, 27])
+    }
+
+    // The sizes of the hidden layers between the board and the moves.
+    pub fn with_hidden(hidden: &[usize]) -> SupervisedNetwork {
+        let mut size = vec![N2 * 3];
+        size.extend_from_slice(hidden);
+        size.push(N2);
+
+        // A softmax output trained with cross-entropy, since the
+        // network picks one move out of nine.
+        let mut nn = NN::new(&size, Activation::Sigmoid, Activation::Softmax, Init::Uniform(0.5));
+        nn.loss = Loss::CrossEntropy;
         
         SupervisedNetwork{ 
-            nn: NN::new(&size),
+            nn,
+            targets: Targets::BestMove,
+            augment: false,
+            average_symmetries: false,
+            rounds: 100,
             epochs: 1,
-            learning_rate: 0.3
+            batch_size: 16,
+            learning_rate: 0.3,
+            schedule: Schedule::Exponential(0.9),
+            log: TrainingLog::init()
         }
     }
 }
 
 impl Network for SupervisedNetwork {
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        self.nn.run(&board.to_binary())
+    }
+
     // Feeds a position into the NN and returns the chosen move.
     fn play(&self, board: &Board) -> usize {
-        let output = self.nn.run(&board.to_binary().to_vec());
+        let output = if self.average_symmetries {
+            self.symmetric_scores(board)
+        } else {
+            self.scores(board)
+        };
 
         // Gets the index of the highest value in output
         let mut best_move: usize = 0;
         let mut highest_score: f64 = -2.0;
 
-        for i in 0..output.len() {
-            if output[i] > highest_score {
-                highest_score = output[i];
+        for (i, &score) in output.iter().enumerate() {
+            if score > highest_score {
+                highest_score = score;
                 best_move = i + 1;
             }
         }
 
-        assert!(best_move >= 1 && best_move <= N2);
+        assert!((1..=N2).contains(&best_move));
 
         best_move
     }
 
     fn train(&mut self) {
-        let mut lr = self.learning_rate;
+        let label = match self.targets {
+            Targets::BestMove => Label::BestMove,
+            Targets::Optimal => Label::Optimal
+        };
 
-        let mut data = Vec::new();
+        let data = training_data(self.augment, |board| label.target(board));
+        self.learn(&data);
+    }
 
-        let ab = AlphaBeta{};
+    fn learn(&mut self, data: &[Example]) {
+        self.log.clear();
 
-        for board in ALL_BOARDS.iter() {
-            let move_ = ab.best_move(&board);
-            
-            // Creates an array of all possible moves and assigns
-            // a one to the chosen move.
-            let mut target: [f64; N2] = [0.0; N2];
-            target[move_ - 1] = 1.0;
+        for round in 0..self.rounds {
+            let lr = self.schedule.rate(self.learning_rate, round, self.rounds);
 
-            // Parses the board for it to be processed.
-            let input_board = board.to_binary();
+            for epoch in 0..self.epochs as usize {
+                let loss = self.nn.train(data, self.batch_size, lr);
 
-            // Adds the tuple of input and target values to the training data.
-            data.push((input_board.to_vec(), target.to_vec()));
+                if self.log.active() {
+                    let accuracy = metrics::accuracy(self, &VALIDATION_BOARDS);
+                    let epoch = round * self.epochs as usize + epoch + 1;
+
+                    self.log.record(Record { epoch, loss, accuracy, learning_rate: lr })
+                        .expect("Couldn't write the training log.");
+                }
+            }
         }
+    }
 
-        for _ in 0..100 {
-            self.nn.train(&data)
-                .halt_condition( HaltCondition::Epochs(self.epochs) )
-                .rate(lr)
-                .go();
+    // Compares the network's moves with perfect play on the boards
+    // it hasn't been trained on.
+    fn test(&self) -> Metrics {
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+        println!("{}", metrics);
+        metrics
+    }
+}
 
-            lr *= 0.9;
+// The inputs and targets of the training boards. With augmentation only
+// one board is taken per position up to symmetry, and its target is
+// moved along to every image of it, so that the targets agree. Both
+// colours are kept, as they are without augmentation.
+pub fn training_data<F>(augment: bool, target: F) -> Vec<Example>
+        where F: Fn(&Board) -> Vec<f64> {
+    let mut data = Vec::new();
+    let mut seen = HashSet::new();
+
+    for board in TRAINING_BOARDS.iter() {
+        if !augment {
+            data.push((board.to_binary().to_vec(), target(board)));
+        } else if seen.insert((board.canonical().0, board.turn)) {
+            for (image, image_target) in symmetries::augment(board, &target(board)) {
+                data.push((image.to_binary().to_vec(), image_target));
+            }
         }
     }
 
-    // Runs the network on all possible positions to see how many
-    // times it gets the correct move.
-    fn test(&self) {
-        let mut correct = 0.0;
-        let ab = AlphaBeta{};
+    data
+}
+
+fn split_boards() -> (Vec<Board>, Vec<Board>) {
+    let mut keys = Vec::new();
 
-        for board in ALL_BOARDS.iter() {
-            let guess = self.play(&board);
-            let move_ = ab.best_move(&board);
+    for board in ALL_BOARDS.iter() {
+        let key = board.canonical().0;
 
-            if move_ == guess {
-                correct += 1.0;
-            }
+        if !keys.contains(&key) {
+            keys.push(key);
         }
-
-        println!("Correct move: {:.5} %", correct * 100.0 / ALL_BOARDS.len() as f64);
     }
+
+    keys.shuffle(&mut StdRng::seed_from_u64(0));
+
+    let n_validation = (keys.len() as f64 * VALIDATION_SHARE) as usize;
+    let validation_keys: HashSet<(u64, u64)> = keys[..n_validation].iter().cloned().collect();
+
+    ALL_BOARDS.iter().cloned().partition(|board| !validation_keys.contains(&board.canonical().0))
 }
 
 // Generates all the possible unfinished boards
@@ -114,7 +202,7 @@ fn leaf_boards(board: Board, boards: &mut Vec<Board>) {
     assert!(board.state() == State::Unfinished);
 
     let moves: Vec<usize> = board.gen_moves();
-    assert!(moves.len() > 0);
+    assert!(!moves.is_empty());
 
     for b in boards.iter() {
         if  board.tiles[board::PLAYER1] == b.tiles[board::PLAYER1] &&
@@ -128,7 +216,7 @@ fn leaf_boards(board: Board, boards: &mut Vec<Board>) {
     boards.push(board.inverse());
 
     for m in moves {
-        let mut new_board = board.clone();
+        let mut new_board = board;
         new_board.make(m);
 
         if new_board.state() == State::Unfinished {
@@ -161,10 +249,31 @@ mod tests {
         ];
 
         for board in test_boards.iter() {
-            boards.contains(board);
+            assert!(boards.contains(board));
         }
     }
 
+    #[test]
+    fn test_augmented_targets() {
+        // The empty squares, equal to the input's last plane, and a value
+        // that doesn't move.
+        let target = |board: &Board| {
+            let mut target = board.to_binary()[N2 * 2..].to_vec();
+            target.push(board.turn as f64);
+            target
+        };
+
+        let augmented = training_data(true, target);
+        assert!(!augmented.is_empty());
+
+        for (input, target) in augmented.iter() {
+            assert_eq!(&target[..N2], &input[N2 * 2..]);
+        }
+
+        let turns: Vec<f64> = augmented.iter().map(|(_, target)| target[N2]).collect();
+        assert!(turns.contains(&0.0) && turns.contains(&1.0));
+    }
+
     #[test]
     fn test_repeated_boards() {
         for i in 0..ALL_BOARDS.len() {
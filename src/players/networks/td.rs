@@ -0,0 +1,205 @@
+extern crate rand;
+use rand::Rng;
+
+use crate::constants::*;
+use crate::game::Progress;
+
+use crate::board::{Board, State};
+
+use crate::players::{
+    networks::network::Network,
+    networks::neural::{NN, Activation, Init, Example},
+    networks::metrics::{self, Metrics},
+    networks::supervised::VALIDATION_BOARDS,
+    alphabeta::AlphaBeta,
+    oracle::Oracle
+};
+
+// TD(λ): a network that values positions for the side to move, between -1
+// and 1, trained by playing itself. Every position is pulled towards its
+// λ-return, a mix of the values of the positions that followed it and the
+// final result. It plays the move leading to the best position.
+pub struct TDNetwork {
+    pub nn: NN,
+    pub games: usize,
+    pub learning_rate: f64,
+    pub lambda: f64,
+
+    // Chance of a random move while training.
+    pub epsilon: f64,
+    pub eval_every: usize
+}
+
+impl TDNetwork {
+    pub fn init() -> TDNetwork {
+        TDNetwork {
+            nn: NN::new(&[27, 54, 1], Activation::Relu, Activation::Tanh, Init::He),
+            games: 50000,
+            learning_rate: 0.05,
+            lambda: 0.7,
+            epsilon: 0.3,
+            eval_every: 5000
+        }
+    }
+
+    // The value of the position for the side to move, exact once the
+    // game is over.
+    pub fn value(&self, board: &Board) -> f64 {
+        if board.state() != State::Unfinished {
+            return board.score() as f64;
+        }
+
+        self.nn.run(&board.to_binary())[0]
+    }
+
+    // The value for the side to move of every move, looking one ply ahead.
+    fn move_values(&self, board: &Board) -> Vec<(usize, f64)> {
+        board.gen_moves().into_iter().map(|move_| {
+            let mut copy = *board;
+            copy.make(move_);
+
+            (move_, -self.value(&copy))
+        }).collect()
+    }
+
+    fn greedy(&self, board: &Board) -> usize {
+        let values = self.move_values(board);
+        let mut best = values[0];
+
+        for &(move_, value) in values.iter() {
+            if value > best.1 {
+                best = (move_, value);
+            }
+        }
+
+        best.0
+    }
+
+    // Plays a game against itself and returns every position with its
+    // λ-return as the target.
+    fn episode(&self) -> Vec<Example> {
+        let mut rng = rand::thread_rng();
+        let mut board = Board { tiles: [0, 0], turn: rng.gen_range(0, 2) };
+
+        // Every position and whether the move made from it was random.
+        let mut history: Vec<(Board, bool)> = Vec::new();
+
+        while board.state() == State::Unfinished {
+            let explore = rng.gen::<f64>() < self.epsilon;
+            let move_ = if explore { board.random_move() } else { self.greedy(&board) };
+
+            history.push((board, explore));
+            board.make(move_);
+        }
+
+        // Going backwards, each return is seen from the other side. After
+        // a random move the position says nothing about the policy, so the
+        // return stops there and bootstraps from the next value alone.
+        let mut next_value = board.score() as f64;
+        let mut next_return = next_value;
+
+        let mut examples = Vec::with_capacity(history.len());
+
+        for (position, explore) in history.into_iter().rev() {
+            let lambda = if explore { 0.0 } else { self.lambda };
+            let target = -((1.0 - lambda) * next_value + lambda * next_return);
+
+            examples.push((position.to_binary().to_vec(), vec![target]));
+
+            next_value = self.value(&position);
+            next_return = target;
+        }
+
+        examples
+    }
+}
+
+impl Network for TDNetwork {
+    // The value of playing every square, and -2 for the ones taken.
+    fn scores(&self, board: &Board) -> Vec<f64> {
+        let mut scores = vec![-2.0; N2];
+
+        for (move_, value) in self.move_values(board) {
+            scores[move_ - 1] = value;
+        }
+
+        scores
+    }
+
+    fn play(&self, board: &Board) -> usize {
+        self.greedy(board)
+    }
+
+    fn train(&mut self) {
+        let ab = AlphaBeta{};
+
+        for game in 1..self.games+1 {
+            let examples = self.episode();
+            self.nn.train(&examples, examples.len(), self.learning_rate);
+
+            if game % self.eval_every == 0 {
+                Progress::measure(&*self, game, &[("alphabeta", &ab)]);
+            }
+        }
+    }
+
+    // Learns the values of examples labelled with one, such as the
+    // PolicyValue ones of a dataset, where it comes after the policy.
+    fn learn(&mut self, examples: &[Example]) {
+        let values: Vec<Example> = examples.iter()
+            .map(|(input, target)| (input.clone(), vec![*target.last().unwrap()]))
+            .collect();
+
+        for _ in 0..20 {
+            self.nn.train(&values, 32, self.learning_rate);
+        }
+    }
+
+    // Besides the moves, checks how often the value rounds to the
+    // perfect-play one.
+    fn test(&self) -> Metrics {
+        let correct = VALIDATION_BOARDS.iter()
+            .filter(|board| self.value(board).round() as isize == Oracle::value(board))
+            .count();
+
+        let metrics = metrics::evaluate(self, &VALIDATION_BOARDS, 3);
+
+        println!("{}", metrics);
+        println!("Correct value: {:.5} %", correct as f64 * 100.0 / VALIDATION_BOARDS.len() as f64);
+
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lambda_returns() {
+        let mut td = TDNetwork::init();
+        td.epsilon = 0.0;
+
+        // With λ = 1 every target is the final result, from the side of
+        // the player to move.
+        td.lambda = 1.0;
+        let examples = td.episode();
+
+        for i in 1..examples.len() {
+            assert_eq!(examples[i].1[0], -examples[i - 1].1[0]);
+        }
+
+        assert!(examples[0].1[0] == 1.0 || examples[0].1[0] == 0.0);
+    }
+
+    #[test]
+    fn test_takes_win() {
+        let td = TDNetwork::init();
+
+        // Finished positions are valued exactly, so a win is always seen.
+        let board = Board { tiles: [0b000000011, 0b000011000], turn: 0 };
+        assert_eq!(td.play(&board), 3);
+        assert_eq!(td.scores(&board)[2], 1.0);
+        assert_eq!(td.scores(&board)[0], -2.0);
+    }
+}
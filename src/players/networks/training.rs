@@ -0,0 +1,180 @@
+use std::f64::consts::PI;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::Write;
+
+// How the learning rate changes over the rounds of training.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Schedule {
+    Constant,
+
+    // Multiplied by the factor after every round.
+    Exponential(f64),
+
+    // Multiplied by the factor after every so many rounds.
+    Step { every: usize, factor: f64 },
+
+    // Half a cosine from the initial rate down to the minimum one, which the
+    // last round trains at.
+    Cosine { min_rate: f64 }
+}
+
+impl Schedule {
+    // The rate for a round, counted from 0.
+    pub fn rate(&self, initial: f64, round: usize, rounds: usize) -> f64 {
+        assert!(round < rounds);
+
+        match *self {
+            Schedule::Constant => initial,
+            Schedule::Exponential(factor) => initial * factor.powi(round as i32),
+            Schedule::Step { every, factor } => {
+                assert!(every > 0);
+                initial * factor.powi((round / every) as i32)
+            }
+            Schedule::Cosine { min_rate } => {
+                // A single round stays at the initial rate.
+                let progress = if rounds == 1 { 0.0 } else { round as f64 / (rounds - 1) as f64 };
+                min_rate + (initial - min_rate) * (1.0 + (PI * progress).cos()) / 2.0
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Csv,
+    JsonLines
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Record {
+    pub epoch: usize,
+    pub loss: f64,
+
+    // Share of the validation boards where an optimal move is played.
+    pub accuracy: f64,
+    pub learning_rate: f64
+}
+
+// Keeps a record of every epoch, and optionally prints it and writes it
+// to a file as it goes. The file is overwritten by a new training.
+#[derive(Clone)]
+pub struct TrainingLog {
+    pub path: Option<String>,
+    pub format: Format,
+    pub print: bool,
+    pub records: Vec<Record>
+}
+
+impl TrainingLog {
+    pub fn init() -> TrainingLog {
+        TrainingLog {
+            path: None,
+            format: Format::Csv,
+            print: false,
+            records: Vec::new()
+        }
+    }
+
+    // Whether the records go anywhere. Measuring the accuracy costs a
+    // pass over the validation boards, so it's skipped otherwise.
+    pub fn active(&self) -> bool {
+        self.print || self.path.is_some()
+    }
+
+    pub fn record(&mut self, record: Record) -> io::Result<()> {
+        if self.print {
+            println!("Epoch {}: loss {:.5}, accuracy {:.2} %, learning rate {:.6}",
+                record.epoch, record.loss, record.accuracy * 100.0, record.learning_rate);
+        }
+
+        if let Some(path) = &self.path {
+            let mut file = if self.records.is_empty() {
+                let mut file = File::create(path)?;
+
+                if self.format == Format::Csv {
+                    writeln!(file, "epoch,loss,accuracy,learning_rate")?;
+                }
+
+                file
+            } else {
+                OpenOptions::new().append(true).open(path)?
+            };
+
+            writeln!(file, "{}", self.line(&record))?;
+        }
+
+        self.records.push(record);
+        Ok(())
+    }
+
+    fn line(&self, record: &Record) -> String {
+        match self.format {
+            Format::Csv => format!("{},{},{},{}",
+                record.epoch, record.loss, record.accuracy, record.learning_rate),
+            Format::JsonLines => format!("{{\"epoch\": {}, \"loss\": {}, \"accuracy\": {}, \"learning_rate\": {}}}",
+                record.epoch, record.loss, record.accuracy, record.learning_rate)
+        }
+    }
+
+    // Forgets the records of a previous training.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_schedules() {
+        assert_eq!(Schedule::Constant.rate(0.1, 7, 10), 0.1);
+        assert!((Schedule::Exponential(0.5).rate(1.0, 3, 10) - 0.125).abs() < 1e-12);
+
+        let step = Schedule::Step { every: 4, factor: 0.1 };
+        assert_eq!(step.rate(1.0, 3, 10), 1.0);
+        assert!((step.rate(1.0, 4, 10) - 0.1).abs() < 1e-12);
+
+        let cosine = Schedule::Cosine { min_rate: 0.01 };
+        assert_eq!(cosine.rate(1.0, 0, 10), 1.0);
+        assert!((cosine.rate(1.0, 5, 11) - 0.505).abs() < 1e-12);
+        assert!((cosine.rate(1.0, 9, 10) - 0.01).abs() < 1e-12);
+        assert_eq!(cosine.rate(1.0, 0, 1), 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_step_every_zero() {
+        Schedule::Step { every: 0, factor: 0.1 }.rate(1.0, 0, 10);
+    }
+
+    #[test]
+    fn test_log_files() {
+        let record = Record { epoch: 1, loss: 0.5, accuracy: 0.25, learning_rate: 0.1 };
+
+        for (format, lines) in [(Format::Csv, 3), (Format::JsonLines, 2)].iter() {
+            let path = env::temp_dir().join(format!("training_log_{:?}.txt", format));
+
+            let mut log = TrainingLog::init();
+            log.path = Some(path.to_str().unwrap().to_string());
+            log.format = *format;
+
+            log.record(record).unwrap();
+            log.record(Record { epoch: 2, ..record }).unwrap();
+
+            let text = fs::read_to_string(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(text.lines().count(), *lines);
+            assert_eq!(log.records.len(), 2);
+
+            if *format == Format::JsonLines {
+                assert_eq!(text.lines().next().unwrap(),
+                    "{\"epoch\": 1, \"loss\": 0.5, \"accuracy\": 0.25, \"learning_rate\": 0.1}");
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+extern crate rand;
+use rand::seq::SliceRandom;
+
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::players::{
+    networks::network::Network,
+    networks::metrics::{self, Metrics},
+    networks::training::Schedule,
+    networks::supervised::{SupervisedNetwork, VALIDATION_BOARDS}
+};
+
+// One way of building and training a SupervisedNetwork.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub hidden: Vec<usize>,
+    pub rounds: usize,
+    pub epochs: u32,
+    pub batch_size: usize,
+    pub learning_rate: f64,
+    pub schedule: Schedule
+}
+
+// The values tried for every parameter.
+pub struct Space {
+    pub hidden: Vec<Vec<usize>>,
+    pub rounds: Vec<usize>,
+    pub epochs: Vec<u32>,
+    pub batch_size: Vec<usize>,
+    pub learning_rate: Vec<f64>,
+    pub schedule: Vec<Schedule>
+}
+
+pub struct Entry {
+    pub config: Config,
+    pub metrics: Metrics
+}
+
+impl Config {
+    pub fn build(&self) -> SupervisedNetwork {
+        let mut net = SupervisedNetwork::with_hidden(&self.hidden);
+
+        net.rounds = self.rounds;
+        net.epochs = self.epochs;
+        net.batch_size = self.batch_size;
+        net.learning_rate = self.learning_rate;
+        net.schedule = self.schedule;
+
+        net
+    }
+}
+
+impl Space {
+    // Around the network's defaults.
+    pub fn init() -> Space {
+        Space {
+            hidden: vec![vec![27], vec![81], vec![81, 27], vec![162, 81]],
+            rounds: vec![50, 100],
+            epochs: vec![1, 2],
+            batch_size: vec![8, 16, 32],
+            learning_rate: vec![0.1, 0.3, 1.0],
+            schedule: vec![
+                Schedule::Exponential(0.9),
+                Schedule::Exponential(0.95),
+                Schedule::Cosine { min_rate: 0.001 }
+            ]
+        }
+    }
+
+    // Every combination of the values.
+    pub fn grid(&self) -> Vec<Config> {
+        let mut configs = Vec::new();
+
+        for hidden in self.hidden.iter() {
+            for &rounds in self.rounds.iter() {
+                for &epochs in self.epochs.iter() {
+                    for &batch_size in self.batch_size.iter() {
+                        for &learning_rate in self.learning_rate.iter() {
+                            for &schedule in self.schedule.iter() {
+                                configs.push(Config {
+                                    hidden: hidden.clone(),
+                                    rounds,
+                                    epochs,
+                                    batch_size,
+                                    learning_rate,
+                                    schedule
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        configs
+    }
+
+    // n combinations, each value picked at random.
+    pub fn sample(&self, n: usize) -> Vec<Config> {
+        let mut rng = rand::thread_rng();
+
+        (0..n).map(|_| Config {
+            hidden: self.hidden.choose(&mut rng).unwrap().clone(),
+            rounds: *self.rounds.choose(&mut rng).unwrap(),
+            epochs: *self.epochs.choose(&mut rng).unwrap(),
+            batch_size: *self.batch_size.choose(&mut rng).unwrap(),
+            learning_rate: *self.learning_rate.choose(&mut rng).unwrap(),
+            schedule: *self.schedule.choose(&mut rng).unwrap()
+        }).collect()
+    }
+}
+
+// Trains every configuration, spread over the threads, and returns them
+// from best to worst: the most optimal moves, then the least score lost.
+pub fn run(configs: Vec<Config>, threads: usize) -> Vec<Entry> {
+    assert!(threads > 0);
+
+    let total = configs.len();
+    let queue = Arc::new(Mutex::new(configs));
+    let entries = Arc::new(Mutex::new(Vec::new()));
+
+    let workers: Vec<_> = (0..threads).map(|_| {
+        let queue = Arc::clone(&queue);
+        let entries = Arc::clone(&entries);
+
+        thread::spawn(move || {
+            loop {
+                let config = match queue.lock().unwrap().pop() {
+                    Some(config) => config,
+                    None => break
+                };
+
+                let mut net = config.build();
+                net.train();
+
+                let metrics = metrics::evaluate(&net, &VALIDATION_BOARDS, 3);
+
+                let mut entries = entries.lock().unwrap();
+                println!("{}/{}: {:.2} % optimal with {:?}",
+                    entries.len() + 1, total, metrics.optimal * 100.0, config);
+
+                entries.push(Entry { config, metrics });
+            }
+        })
+    }).collect();
+
+    for worker in workers {
+        worker.join().expect("A training thread panicked.");
+    }
+
+    let mut entries = Arc::try_unwrap(entries).ok().unwrap().into_inner().unwrap();
+    rank(&mut entries);
+    entries
+}
+
+fn rank(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| {
+        b.metrics.optimal.partial_cmp(&a.metrics.optimal).unwrap()
+            .then(a.metrics.score_loss.partial_cmp(&b.metrics.score_loss).unwrap())
+    });
+}
+
+// A CSV file with a line per configuration, the best one first.
+pub fn write_leaderboard(entries: &[Entry], path: &str) -> io::Result<()> {
+    let mut contents = String::from(
+        "rank,optimal,top1,topk,illegal,score_loss,hidden,rounds,epochs,batch_size,learning_rate,schedule\n");
+
+    for (i, entry) in entries.iter().enumerate() {
+        let (config, metrics) = (&entry.config, &entry.metrics);
+        let hidden: Vec<String> = config.hidden.iter().map(|size| size.to_string()).collect();
+
+        contents.push_str(&format!("{},{},{},{},{},{},{},{},{},{},{},\"{:?}\"\n",
+            i + 1, metrics.optimal, metrics.top1, metrics.topk, metrics.illegal, metrics.score_loss,
+            hidden.join("-"), config.rounds, config.epochs, config.batch_size,
+            config.learning_rate, config.schedule));
+    }
+
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let space = Space::init();
+        let grid = space.grid();
+
+        assert_eq!(grid.len(), 4 * 2 * 2 * 3 * 3 * 3);
+        assert_eq!(space.sample(5).len(), 5);
+
+        // The defaults are in the grid.
+        let net = SupervisedNetwork::init();
+        assert!(grid.iter().any(|c| c.hidden == vec![81, 27] && c.rounds == net.rounds &&
+            c.epochs == net.epochs && c.batch_size == net.batch_size &&
+            c.learning_rate == net.learning_rate && c.schedule == net.schedule));
+    }
+
+    #[test]
+    fn test_run() {
+        let config = Config {
+            hidden: vec![9],
+            rounds: 1,
+            epochs: 1,
+            batch_size: 64,
+            learning_rate: 0.1,
+            schedule: Schedule::Constant
+        };
+
+        let entries = run(vec![config.clone(), Config { hidden: vec![18], ..config }], 2);
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].metrics.optimal >= entries[1].metrics.optimal);
+        assert_eq!(entries[0].config.build().nn.layers.len(), 2);
+
+        let path = std::env::temp_dir().join("leaderboard_test.csv");
+        let path = path.to_str().unwrap();
+
+        write_leaderboard(&entries, path).unwrap();
+        let contents = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let lines: Vec<Vec<&str>> = contents.lines().map(|line| line.split(',').collect()).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0][..2], ["rank", "optimal"]);
+
+        for (i, (line, entry)) in lines[1..].iter().zip(entries.iter()).enumerate() {
+            assert_eq!(line.len(), lines[0].len());
+            assert_eq!(line[0], (i + 1).to_string());
+            assert_eq!(line[1].parse::<f64>().unwrap(), entry.metrics.optimal);
+            assert_eq!(line[6], entry.config.hidden[0].to_string());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+extern crate rand;
+use rand::seq::SliceRandom;
+
+use std::collections::HashMap;
+
+use crate::constants::*;
+use crate::board::{Board, State};
+use crate::players::{player::Player, tablebase::TABLEBASE};
+
+// Knows the game-theoretic value of every position: 1 for a win, 0 for a
+// draw and -1 for a loss, from the side to move's point of view. As a
+// player it picks at random between all of the optimal moves.
+pub struct Oracle;
+
+impl Oracle {
+    // Looked up in the tablebase, or solved on the spot for positions
+    // that can't come up in a game.
+    pub fn value(board: &Board) -> isize {
+        match TABLEBASE.probe(board) {
+            Some((value, _)) => value,
+            None => solve((board.tiles[board.turn], board.tiles[board.turn ^ 1]), &mut HashMap::new())
+        }
+    }
+
+    // The value for the side to move of playing each square, or None
+    // if the square is taken. Nothing can be played on a finished board.
+    pub fn move_values(board: &Board) -> [Option<isize>; N2] {
+        let mut values: [Option<isize>; N2] = [None; N2];
+
+        if board.state() != State::Unfinished {
+            return values;
+        }
+
+        for move_ in board.gen_moves() {
+            let mut copy = *board;
+            copy.make(move_);
+
+            values[move_ - 1] = Some(-Oracle::value(&copy));
+        }
+
+        values
+    }
+
+    // Same as move_values, but illegal moves lose the game.
+    pub fn move_value(board: &Board, move_: usize) -> isize {
+        if !(1..=N2).contains(&move_) {
+            return -1;
+        }
+
+        Oracle::move_values(board)[move_ - 1].unwrap_or(-1)
+    }
+
+    // Every move that keeps the value of the position.
+    pub fn optimal_moves(board: &Board) -> Vec<usize> {
+        let values = Oracle::move_values(board);
+        let best = values.iter().filter_map(|&v| v).max();
+
+        (1..N2+1).filter(|&m| best.is_some() && values[m - 1] == best).collect()
+    }
+
+    // The optimal moves share the probability evenly.
+    pub fn soft_target(board: &Board) -> [f64; N2] {
+        let moves = Oracle::optimal_moves(board);
+        assert!(!moves.is_empty());
+
+        let mut target: [f64; N2] = [0.0; N2];
+
+        for &m in moves.iter() {
+            target[m - 1] = 1.0 / moves.len() as f64;
+        }
+
+        target
+    }
+}
+
+impl Player for Oracle {
+    fn best_move(&self, board: &Board) -> usize {
+        *Oracle::optimal_moves(board).choose(&mut rand::thread_rng()).unwrap()
+    }
+
+    fn move_scores(&self, board: &Board) -> Vec<f64> {
+        Oracle::move_values(board).iter().map(|value| value.unwrap_or(-1) as f64).collect()
+    }
+}
+
+// Negamax over every position, remembering the ones already solved.
+fn solve(key: (u64, u64), values: &mut HashMap<(u64, u64), isize>) -> isize {
+    if let Some(value) = values.get(&key) {
+        return *value;
+    }
+
+    let board = Board { tiles: [key.0, key.1], turn: 0 };
+
+    let value = if board.state() != State::Unfinished {
+        board.score()
+    } else {
+        board.gen_moves().into_iter()
+            .map(|m| -solve((key.1, key.0 | 1 << (m - 1)), values))
+            .max().unwrap()
+    };
+
+    values.insert(key, value);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::alphabeta::AlphaBeta;
+    use crate::players::networks::supervised::ALL_BOARDS;
+
+    #[test]
+    fn test_agrees_with_alphabeta() {
+        for board in ALL_BOARDS.iter() {
+            let value = AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE).0.signum();
+            let moves = Oracle::optimal_moves(board);
+
+            assert_eq!(Oracle::value(board), value);
+            assert!(moves.contains(&AlphaBeta{}.best_move(board)));
+
+            for &m in moves.iter() {
+                assert_eq!(Oracle::move_value(board, m), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimal_moves() {
+        // Every opening move draws.
+        let empty = Board { tiles: [0, 0], turn: 0 };
+        assert_eq!(Oracle::value(&empty), 0);
+        assert_eq!(Oracle::optimal_moves(&empty), (1..N2+1).collect::<Vec<usize>>());
+
+        // 3 and 9 win straight away and 7 a move later, with a double threat.
+        let board = Board { tiles: [0b000010011, 0b010101000], turn: 0 };
+        let target = Oracle::soft_target(&board);
+
+        assert_eq!(Oracle::optimal_moves(&board), vec![3, 7, 9]);
+        assert_eq!(Oracle::move_values(&board)[0], None);
+        assert!((target[6] - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,238 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::constants::*;
+use crate::bitboards::WINNING_STATES;
+use crate::players::player::Player;
+use crate::board::{Board, State};
+
+// The ways of ordering the moves, which can be turned on one at a time to
+// see what each is worth.
+#[derive(Clone, Copy, Debug)]
+pub struct Heuristics {
+    // The best move found the last time the position was searched.
+    pub transpositions: bool,
+
+    // Moves that caused a cutoff with the same number of tiles on the board.
+    pub killers: bool,
+
+    // Moves that caused cutoffs anywhere, weighed by the depth left.
+    pub history: bool,
+
+    // The squares closest to the centre first, then those on more lines.
+    pub centre: bool
+}
+
+impl Heuristics {
+    pub fn all() -> Heuristics {
+        Heuristics { transpositions: true, killers: true, history: true, centre: true }
+    }
+
+    pub fn none() -> Heuristics {
+        Heuristics { transpositions: false, killers: false, history: false, centre: false }
+    }
+}
+
+// An alpha-beta search, scored like AlphaBeta, that keeps what it learns
+// about the moves to try the ones likely to cause a cutoff first.
+pub struct Search {
+    pub heuristics: Heuristics,
+
+    // Positions visited so far.
+    pub nodes: usize,
+
+    best_moves: HashMap<(u64, u64), usize>,
+    killers: [[usize; 2]; N2 + 1],
+    history: [[usize; N2]; 2]
+}
+
+impl Search {
+    pub fn init(heuristics: Heuristics) -> Search {
+        Search {
+            heuristics,
+            nodes: 0,
+            best_moves: HashMap::new(),
+            killers: [[0; 2]; N2 + 1],
+            history: [[0; N2]; 2]
+        }
+    }
+
+    pub fn run(&mut self, board: &Board, mut alpha: isize, beta: isize) -> (isize, usize) {
+        self.nodes += 1;
+
+        if board.state() != State::Unfinished {
+            return (board.distance_score(), 0);
+        }
+
+        let mut moves: Vec<usize> = board.gen_moves();
+        assert!(!moves.is_empty());
+
+        let forced_move = board.find_forced();
+
+        if forced_move != 0 {
+            moves.clear();
+            moves.push(forced_move);
+        } else {
+            self.order(board, &mut moves);
+        }
+
+        let key = (board.tiles[board.turn], board.tiles[board.turn ^ 1]);
+        let tiles = N2 - board.empty().count_ones() as usize;
+
+        let mut max_score: isize = -MAX_SCORE;
+        let mut best_move: usize = 0;
+
+        for move_ in moves {
+            let mut copy = *board;
+            copy.make(move_);
+
+            let score = -self.run(&copy, -beta, -alpha).0;
+
+            if score > max_score {
+                max_score = score;
+                best_move = move_;
+
+                if max_score >= beta {
+                    self.cutoff(board, tiles, move_);
+                    break;
+                }
+
+                if max_score > alpha {
+                    alpha = max_score;
+                }
+            }
+        }
+
+        assert!((1..=N2).contains(&best_move));
+        assert!(max_score > -MAX_SCORE);
+
+        self.best_moves.insert(key, best_move);
+
+        (max_score, best_move)
+    }
+
+    // Remembers a move that refuted the opponent's last one.
+    fn cutoff(&mut self, board: &Board, tiles: usize, move_: usize) {
+        let killers = &mut self.killers[tiles];
+
+        if killers[0] != move_ {
+            killers[1] = killers[0];
+            killers[0] = move_;
+        }
+
+        let depth = N2 - tiles;
+        self.history[board.turn][move_ - 1] += depth * depth;
+    }
+
+    // Sorts the moves from the most to the least promising. On a board this
+    // small where a square is matters more than the history, which only
+    // breaks the ties. The stable sort leaves the moves in square order
+    // when no heuristic tells them apart.
+    fn order(&self, board: &Board, moves: &mut [usize]) {
+        let h = self.heuristics;
+        let tiles = N2 - board.empty().count_ones() as usize;
+
+        let best_move = if h.transpositions {
+            self.best_moves.get(&(board.tiles[board.turn], board.tiles[board.turn ^ 1])).cloned()
+        } else {
+            None
+        };
+
+        moves.sort_by_key(|&move_| Reverse((
+            best_move == Some(move_),
+            h.killers && self.killers[tiles].contains(&move_),
+            if h.centre { centrality(move_) } else { (0, 0) },
+            if h.history { self.history[board.turn][move_ - 1] } else { 0 }
+        )));
+    }
+}
+
+// How central a square is: minus its distance from the centre, counting
+// diagonal steps as one, and the number of lines through it.
+pub fn centrality(move_: usize) -> (isize, usize) {
+    let (row, col) = ((move_ - 1) / N, (move_ - 1) % N);
+
+    // Doubled, so that the centre of an even board is a whole number.
+    let distance = (2 * row as isize - (N as isize - 1)).abs()
+        .max((2 * col as isize - (N as isize - 1)).abs());
+
+    let lines = WINNING_STATES.iter().filter(|&comb| comb & (1 << (move_ - 1)) != 0).count();
+
+    (-distance, lines)
+}
+
+// Plays like AlphaBeta, ordering the moves with every heuristic.
+pub struct OrderedAlphaBeta;
+
+impl Player for OrderedAlphaBeta {
+    fn best_move(&self, board: &Board) -> usize {
+        Search::init(Heuristics::all()).run(board, -MAX_SCORE, MAX_SCORE).1
+    }
+}
+
+// Counts the positions searched to solve every board with each heuristic
+// alone, all of them and none.
+pub fn benchmark(boards: &[Board]) -> Vec<(&'static str, usize)> {
+    let configurations = [
+        ("none", Heuristics::none()),
+        ("transpositions", Heuristics { transpositions: true, ..Heuristics::none() }),
+        ("killers", Heuristics { killers: true, ..Heuristics::none() }),
+        ("history", Heuristics { history: true, ..Heuristics::none() }),
+        ("centre", Heuristics { centre: true, ..Heuristics::none() }),
+        ("all", Heuristics::all())
+    ];
+
+    configurations.iter().map(|&(name, heuristics)| {
+        let nodes: usize = boards.iter().map(|board| {
+            let mut search = Search::init(heuristics);
+            search.run(board, -MAX_SCORE, MAX_SCORE);
+            search.nodes
+        }).sum();
+
+        println!("{:>15}: {} nodes", name, nodes);
+
+        (name, nodes)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::alphabeta::AlphaBeta;
+    use crate::players::networks::supervised::ALL_BOARDS;
+
+    #[test]
+    fn test_same_scores() {
+        for board in ALL_BOARDS.iter().step_by(7) {
+            let score = AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE).0;
+
+            for &heuristics in [Heuristics::none(), Heuristics::all()].iter() {
+                assert_eq!(Search::init(heuristics).run(board, -MAX_SCORE, MAX_SCORE).0, score);
+            }
+        }
+    }
+
+    #[test]
+    fn test_centre_first() {
+        let mut moves: Vec<usize> = (1..N2+1).collect();
+        Search::init(Heuristics { centre: true, ..Heuristics::none() })
+            .order(&Board { tiles: [0, 0], turn: 0 }, &mut moves);
+
+        assert_eq!(moves, vec![5, 1, 3, 7, 9, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn test_fewer_nodes() {
+        let empty = Board { tiles: [0, 0], turn: 0 };
+        let results = benchmark(&[empty]);
+
+        let nodes = |name| results.iter().find(|r| r.0 == name).unwrap().1;
+
+        for &(name, n) in results.iter() {
+            assert!(n <= nodes("none"), "{} searched more than square order", name);
+            assert!(nodes("all") <= n);
+        }
+
+        assert!(nodes("all") * 4 < nodes("none"));
+    }
+}
@@ -1,5 +1,18 @@
+use crate::constants::*;
 use crate::board::Board;
 
 pub trait Player {
     fn best_move(&self, board: &Board) -> usize;
-}
\ No newline at end of file
+
+    // How much the player likes every square, higher being better. Only
+    // the empty ones count. Players that can't tell only rate their move.
+    fn move_scores(&self, board: &Board) -> Vec<f64> {
+        let mut scores = vec![0.0; N2];
+        scores[self.best_move(board) - 1] = 1.0;
+        scores
+    }
+
+    // Called with the final board once a game is over, so that players
+    // can learn from it.
+    fn game_over(&self, _board: &Board) {}
+}
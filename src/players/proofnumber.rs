@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+
+use crate::board::{Board, State};
+use crate::players::player::Player;
+
+// Bigger than any proof or disproof number of an unsolved position.
+const INFINITY: usize = 1 << 40;
+
+// What the side to move at the root tries to prove.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    Win,
+    NotLose
+}
+
+// The value of a position for the side to move, 1, 0 or -1 as the
+// Oracle's, with the move that starts the strategy achieving it, if it
+// isn't a loss, and what it took to find.
+#[derive(Clone, Copy, Debug)]
+pub struct Proof {
+    pub value: isize,
+    pub move_: Option<usize>,
+
+    // Positions expanded by the search.
+    pub nodes: usize,
+
+    // Positions in the proof: every answer of the side that fails and one
+    // move of the side that succeeds.
+    pub size: usize
+}
+
+// Proof-number search, which always expands the position that's cheapest
+// to prove or disprove the goal through, counted in unsolved positions.
+// Numbers are kept for the side to move, as φ and δ: the cost of proving
+// that it achieves its goal, or that the opponent achieves theirs, and
+// the cost of disproving it. The depth-first version (df-pn) searches
+// under thresholds and keeps the numbers in a table instead of a tree.
+pub struct ProofNumber {
+    pub depth_first: bool
+}
+
+// The root's side achieves the goal when the game ends as it wants.
+fn achieved(board: &Board, attacker: usize, goal: Goal) -> bool {
+    let score = if board.turn == attacker { board.score() } else { -board.score() };
+
+    match goal {
+        Goal::Win => score == 1,
+        Goal::NotLose => score >= 0
+    }
+}
+
+// The φ and δ of a position with no children yet.
+fn evaluate(board: &Board, attacker: usize, goal: Goal) -> (usize, usize) {
+    if board.state() == State::Unfinished {
+        return (1, 1);
+    }
+
+    // The goal is the attacker's, and the defender's is its opposite.
+    if achieved(board, attacker, goal) == (board.turn == attacker) {
+        (0, INFINITY)
+    } else {
+        (INFINITY, 0)
+    }
+}
+
+// The side to move needs a single child where the opponent fails, and
+// fails itself only once it does in every child.
+fn combine<I: Iterator<Item = (usize, usize)>>(children: I) -> (usize, usize) {
+    let mut phi = INFINITY;
+    let mut delta = 0;
+
+    for (child_phi, child_delta) in children {
+        phi = phi.min(child_delta);
+        delta = (delta + child_phi).min(INFINITY);
+    }
+
+    (phi, delta)
+}
+
+struct Node {
+    board: Board,
+    parent: Option<usize>,
+    children: Vec<(usize, usize)>,
+    phi: usize,
+    delta: usize
+}
+
+// Best-first search over an explicit tree.
+struct Tree {
+    nodes: Vec<Node>,
+    attacker: usize,
+    goal: Goal
+}
+
+impl Tree {
+    fn prove(board: &Board, goal: Goal) -> Tree {
+        let (phi, delta) = evaluate(board, board.turn, goal);
+        let root = Node { board: *board, parent: None, children: Vec::new(), phi, delta };
+        let mut tree = Tree { nodes: vec![root], attacker: board.turn, goal };
+
+        while tree.nodes[0].phi != 0 && tree.nodes[0].delta != 0 {
+            let node = tree.most_proving();
+            tree.expand(node);
+            tree.update(node);
+        }
+
+        tree
+    }
+
+    // Down from the root through the children that set the φ of their
+    // parent, which are the cheapest to change it.
+    fn most_proving(&self) -> usize {
+        let mut node = 0;
+
+        while !self.nodes[node].children.is_empty() {
+            let phi = self.nodes[node].phi;
+
+            node = self.nodes[node].children.iter()
+                .map(|&(_, child)| child)
+                .find(|&child| self.nodes[child].delta == phi)
+                .unwrap();
+        }
+
+        node
+    }
+
+    fn expand(&mut self, node: usize) {
+        let board = self.nodes[node].board;
+
+        for move_ in board.gen_moves() {
+            let mut copy = board;
+            copy.make(move_);
+
+            let (phi, delta) = evaluate(&copy, self.attacker, self.goal);
+            self.nodes.push(Node { board: copy, parent: Some(node), children: Vec::new(), phi, delta });
+
+            let child = self.nodes.len() - 1;
+            self.nodes[node].children.push((move_, child));
+        }
+    }
+
+    // Back up to the root, stopping where nothing changes.
+    fn update(&mut self, mut node: usize) {
+        loop {
+            let (phi, delta) = combine(self.nodes[node].children.iter()
+                .map(|&(_, child)| (self.nodes[child].phi, self.nodes[child].delta)));
+
+            let unchanged = phi == self.nodes[node].phi && delta == self.nodes[node].delta;
+            self.nodes[node].phi = phi;
+            self.nodes[node].delta = delta;
+
+            match self.nodes[node].parent {
+                Some(parent) if !unchanged => node = parent,
+                _ => break
+            }
+        }
+    }
+
+    fn size(&self, node: usize) -> usize {
+        let children = self.nodes[node].children.iter().map(|&(_, child)| child);
+
+        if self.nodes[node].phi == 0 {
+            1 + children.filter(|&child| self.nodes[child].delta == 0).map(|child| self.size(child)).next().unwrap_or(0)
+        } else {
+            1 + children.map(|child| self.size(child)).sum::<usize>()
+        }
+    }
+
+    fn result(&self) -> (bool, Option<usize>, usize, usize) {
+        let root = &self.nodes[0];
+        let move_ = root.children.iter().find(|&&(_, child)| self.nodes[child].delta == 0).map(|&(m, _)| m);
+
+        let expanded = self.nodes.iter().filter(|node| !node.children.is_empty()).count();
+
+        (root.phi == 0, if root.phi == 0 { move_ } else { None }, expanded, self.size(0))
+    }
+}
+
+// Depth-first search with a transposition table.
+struct DepthFirst {
+    table: HashMap<(u64, u64, usize), (usize, usize)>,
+    attacker: usize,
+    goal: Goal,
+    nodes: usize
+}
+
+impl DepthFirst {
+    fn prove(board: &Board, goal: Goal) -> DepthFirst {
+        let mut search = DepthFirst { table: HashMap::new(), attacker: board.turn, goal, nodes: 0 };
+        search.mid(board, INFINITY, INFINITY);
+        search
+    }
+
+    fn key(board: &Board) -> (u64, u64, usize) {
+        (board.tiles[0], board.tiles[1], board.turn)
+    }
+
+    fn lookup(&self, board: &Board) -> (usize, usize) {
+        match self.table.get(&DepthFirst::key(board)) {
+            Some(&numbers) => numbers,
+            None => evaluate(board, self.attacker, self.goal)
+        }
+    }
+
+    fn children(board: &Board) -> Vec<(usize, Board)> {
+        board.gen_moves().into_iter().map(|move_| {
+            let mut copy = *board;
+            copy.make(move_);
+            (move_, copy)
+        }).collect()
+    }
+
+    // Searches until the φ or δ of the board reaches its threshold. The
+    // child with the smallest δ is searched until it's no longer the
+    // smallest or the parent passes its own thresholds.
+    fn mid(&mut self, board: &Board, phi_threshold: usize, delta_threshold: usize) {
+        let (phi, delta) = self.lookup(board);
+
+        if phi >= phi_threshold || delta >= delta_threshold {
+            return;
+        }
+
+        if board.state() != State::Unfinished {
+            self.table.insert(DepthFirst::key(board), (phi, delta));
+            return;
+        }
+
+        self.nodes += 1;
+        let children = DepthFirst::children(board);
+
+        loop {
+            let numbers: Vec<(usize, usize)> = children.iter().map(|(_, child)| self.lookup(child)).collect();
+            let (phi, delta) = combine(numbers.iter().cloned());
+
+            if phi >= phi_threshold || delta >= delta_threshold {
+                self.table.insert(DepthFirst::key(board), (phi, delta));
+                return;
+            }
+
+            let mut best = 0;
+            let mut second_delta = INFINITY;
+
+            for i in 1..numbers.len() {
+                if numbers[i].1 < numbers[best].1 {
+                    second_delta = numbers[best].1;
+                    best = i;
+                } else if numbers[i].1 < second_delta {
+                    second_delta = numbers[i].1;
+                }
+            }
+
+            let (child_phi, _) = numbers[best];
+
+            self.mid(&children[best].1,
+                delta_threshold - (delta - child_phi),
+                phi_threshold.min(second_delta + 1));
+        }
+    }
+
+    fn size(&self, board: &Board) -> usize {
+        if board.state() != State::Unfinished {
+            return 1;
+        }
+
+        let children = DepthFirst::children(board);
+
+        if self.lookup(board).0 == 0 {
+            1 + children.iter().find(|(_, child)| self.lookup(child).1 == 0).map(|(_, child)| self.size(child)).unwrap()
+        } else {
+            1 + children.iter().map(|(_, child)| self.size(child)).sum::<usize>()
+        }
+    }
+
+    fn result(&self, board: &Board) -> (bool, Option<usize>, usize, usize) {
+        let proven = self.lookup(board).0 == 0;
+        let move_ = DepthFirst::children(board).into_iter()
+            .find(|(_, child)| self.lookup(child).1 == 0)
+            .map(|(move_, _)| move_);
+
+        (proven, if proven { move_ } else { None }, self.nodes, self.size(board))
+    }
+}
+
+impl ProofNumber {
+    pub fn init() -> ProofNumber {
+        ProofNumber { depth_first: true }
+    }
+
+    // Whether the side to move can achieve the goal, with the first move
+    // of a strategy that does, the positions expanded and the proof size.
+    pub fn prove(&self, board: &Board, goal: Goal) -> (bool, Option<usize>, usize, usize) {
+        assert!(board.state() == State::Unfinished);
+
+        if self.depth_first {
+            DepthFirst::prove(board, goal).result(board)
+        } else {
+            Tree::prove(board, goal).result()
+        }
+    }
+
+    // A win is tried first and then a draw. The size of a draw is that
+    // of both proofs, as it takes failing to win and not losing.
+    pub fn solve(&self, board: &Board) -> Proof {
+        let (win, move_, nodes, size) = self.prove(board, Goal::Win);
+
+        if win {
+            return Proof { value: 1, move_, nodes, size };
+        }
+
+        let (draw, draw_move, draw_nodes, draw_size) = self.prove(board, Goal::NotLose);
+
+        Proof {
+            value: if draw { 0 } else { -1 },
+            move_: draw_move,
+            nodes: nodes + draw_nodes,
+            size: size + draw_size
+        }
+    }
+}
+
+// Lost positions have no strategy, so any move will do.
+impl Player for ProofNumber {
+    fn best_move(&self, board: &Board) -> usize {
+        self.solve(board).move_.unwrap_or_else(|| board.gen_moves()[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::oracle::Oracle;
+    use crate::players::networks::supervised::ALL_BOARDS;
+
+    #[test]
+    fn test_agrees_with_oracle() {
+        let solvers = [ProofNumber { depth_first: false }, ProofNumber { depth_first: true }];
+
+        for board in ALL_BOARDS.iter().step_by(3).filter(|b| b.state() == State::Unfinished) {
+            let value = Oracle::value(board);
+
+            for solver in solvers.iter() {
+                let proof = solver.solve(board);
+                assert_eq!(proof.value, value);
+
+                match proof.move_ {
+                    Some(move_) => assert_eq!(Oracle::move_value(board, move_), value),
+                    None => assert_eq!(value, -1)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_size() {
+        let empty = Board { tiles: [0, 0], turn: 0 };
+
+        for &depth_first in [false, true].iter() {
+            let proof = ProofNumber { depth_first }.solve(&empty);
+
+            assert_eq!(proof.value, 0);
+            assert!(proof.size > 1 && proof.nodes > 1);
+        }
+
+        // A single move wins.
+        let board = Board { tiles: [0b000001001, 0b000000110], turn: 0 };
+        let proof = ProofNumber::init().solve(&board);
+
+        assert_eq!((proof.value, proof.move_, proof.size), (1, Some(7), 2));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::constants::*;
+use crate::game;
+use crate::players::player::Player;
+use crate::players::evaluator::Evaluator;
+use crate::board::{Board, State};
+
+// MCTS where the selection is guided by the evaluator's priors (PUCT)
+// and the leaves are valued by the evaluator instead of random rollouts.
+pub struct PUCT<E: Evaluator> {
+    pub evaluator: E,
+    pub n: usize,
+    pub c_puct: f64
+}
+
+struct Node {
+    move_: usize,
+    prior: f64,
+    children: Vec<Node>,
+
+    // Seen from the perspective of the player that made move_.
+    value_sum: f64,
+    visits: f64
+}
+
+impl Node {
+    fn new(move_: usize, prior: f64) -> Node {
+        Node { move_, prior, children: Vec::new(), value_sum: 0.0, visits: 0.0 }
+    }
+
+    // Returns the value obtained by the player that made move_.
+    fn simulate<E: Evaluator>(&mut self, board: &mut Board, evaluator: &E, c_puct: f64) -> f64 {
+        let value = if board.state() != State::Unfinished {
+            // The score is given from the perspective of the side to move.
+            -board.score() as f64
+        } else if self.children.is_empty() {
+            let (priors, value) = evaluator.evaluate(board);
+
+            for m in board.gen_moves() {
+                self.children.push(Node::new(m, priors[m - 1]));
+            }
+
+            -value
+        } else {
+            let child = self.select(c_puct);
+            board.make(child.move_);
+
+            -child.simulate(board, evaluator, c_puct)
+        };
+
+        self.visits += 1.0;
+        self.value_sum += value;
+
+        value
+    }
+
+    fn select(&mut self, c_puct: f64) -> &mut Node {
+        let sqrt_visits = self.visits.sqrt();
+
+        let mut max_score: f64 = f64::MIN;
+        let mut best_child: Option<&mut Node> = None;
+
+        for child in self.children.iter_mut() {
+            let q = if child.visits > 0.0 { child.value_sum / child.visits } else { 0.0 };
+            let u = c_puct * child.prior * sqrt_visits / (1.0 + child.visits);
+
+            if q + u > max_score {
+                max_score = q + u;
+                best_child = Some(child);
+            }
+        }
+
+        best_child.expect("Selecting on a node without children.")
+    }
+}
+
+// Runs n simulations from the board and returns the share of visits of
+// every square. The root priors are mixed with a uniform distribution
+// by the noise factor to encourage exploration during self-play.
+pub fn search<E: Evaluator>(evaluator: &E, board: &Board, n: usize, c_puct: f64, noise: f64) -> [f64; N2] {
+    assert!(board.state() == State::Unfinished);
+    assert!(n > 0);
+
+    let mut root = Node::new(0, 1.0);
+    root.simulate(&mut board.clone(), evaluator, c_puct);
+
+    let uniform = 1.0 / root.children.len() as f64;
+
+    for child in root.children.iter_mut() {
+        child.prior = (1.0 - noise) * child.prior + noise * uniform;
+    }
+
+    for _ in 0..n {
+        root.simulate(&mut board.clone(), evaluator, c_puct);
+    }
+
+    let mut policy: [f64; N2] = [0.0; N2];
+    let total: f64 = root.children.iter().map(|child| child.visits).sum();
+
+    for child in root.children.iter() {
+        policy[child.move_ - 1] = child.visits / total;
+    }
+
+    policy
+}
+
+impl<E: Evaluator> Player for PUCT<E> {
+    fn best_move(&self, board: &Board) -> usize {
+        if board.state() != State::Unfinished {
+            // The game has ended so there is no best move.
+            return 0;
+        }
+
+        let policy = search(&self.evaluator, board, self.n, self.c_puct, 0.0);
+        game::best_legal_move(board, &policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Leaves the search to find the wins and losses on its own.
+    struct Uniform;
+
+    impl Evaluator for Uniform {
+        fn evaluate(&self, _board: &Board) -> ([f64; N2], f64) {
+            ([1.0 / N2 as f64; N2], 0.0)
+        }
+    }
+
+    #[test]
+    fn test_forced_moves() {
+        let puct = PUCT { evaluator: Uniform, n: 400, c_puct: 1.5 };
+
+        let tests = [
+            (Board { tiles: [0b000000011, 0b000011000], turn: 0 }, 3),
+            (Board { tiles: [0b011000100, 0b100100001], turn: 0 }, 5),
+            (Board { tiles: [0b010000001, 0b000011000], turn: 0 }, 6),
+            (Board { tiles: [0b000010000, 0b000000011], turn: 0 }, 3)
+        ];
+
+        for test in tests.iter() {
+            let board = test.0;
+
+            assert_eq!(puct.best_move(&board), test.1);
+            assert_eq!(puct.best_move(&board.inverse()), test.1);
+        }
+    }
+
+    #[test]
+    fn test_search_policy() {
+        let board = Board { tiles: [0b000010000, 0b000000011], turn: 0 };
+        let policy = search(&Uniform, &board, 100, 1.5, 0.25);
+
+        let total: f64 = policy.iter().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Occupied squares are never visited.
+        assert_eq!(policy[0], 0.0);
+        assert_eq!(policy[1], 0.0);
+        assert_eq!(policy[4], 0.0);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use crate::constants::*;
+use crate::players::player::Player;
+use crate::players::ordered::{self, Heuristics};
+use crate::board::{Board, State};
+
+// Principal Variation Search: the first move is searched with the whole
+// window and the rest with a null window that only proves them worse,
+// searching again the ones that turn out better. The depth grows one ply
+// at a time, each iteration looking at the previous best moves first and
+// starting with a window around the previous score.
+pub struct PVS {
+    // Half the width of the aspiration window, or None for the whole one.
+    pub window: Option<isize>,
+    pub deepening: bool
+}
+
+// What a search cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub nodes: usize,
+
+    // Null-window searches that had to be repeated with the whole window.
+    pub re_searches: usize,
+
+    // Iterations whose score fell outside the aspiration window.
+    pub failed_windows: usize
+}
+
+struct Search {
+    stats: Stats,
+    best_moves: HashMap<(u64, u64), usize>
+}
+
+impl Search {
+    // Positions past the depth are worth 0, as a draw. Won and lost games
+    // are exact, since they can't be undone by searching deeper.
+    fn run(&mut self, board: &Board, depth: usize, mut alpha: isize, beta: isize) -> (isize, usize) {
+        self.stats.nodes += 1;
+
+        if board.state() != State::Unfinished {
+            return (board.distance_score(), 0);
+        }
+
+        if depth == 0 {
+            return (0, 0);
+        }
+
+        let key = (board.tiles[board.turn], board.tiles[board.turn ^ 1]);
+        let mut moves: Vec<usize> = board.gen_moves();
+
+        let forced_move = board.find_forced();
+
+        if forced_move != 0 {
+            moves.clear();
+            moves.push(forced_move);
+        } else {
+            let best_move = self.best_moves.get(&key).cloned();
+            moves.sort_by_key(|&move_| (best_move != Some(move_), std::cmp::Reverse(ordered::centrality(move_))));
+        }
+
+        let mut max_score: isize = -MAX_SCORE;
+        let mut best_move: usize = 0;
+
+        for (i, &move_) in moves.iter().enumerate() {
+            let mut copy = *board;
+            copy.make(move_);
+
+            let mut score;
+
+            if i == 0 {
+                score = -self.run(&copy, depth - 1, -beta, -alpha).0;
+            } else {
+                score = -self.run(&copy, depth - 1, -alpha - 1, -alpha).0;
+
+                if score > alpha && score < beta {
+                    self.stats.re_searches += 1;
+                    score = -self.run(&copy, depth - 1, -beta, -alpha).0;
+                }
+            }
+
+            if score > max_score {
+                max_score = score;
+                best_move = move_;
+
+                if max_score >= beta {
+                    break;
+                }
+
+                if max_score > alpha {
+                    alpha = max_score;
+                }
+            }
+        }
+
+        assert!((1..=N2).contains(&best_move));
+
+        self.best_moves.insert(key, best_move);
+
+        (max_score, best_move)
+    }
+}
+
+impl PVS {
+    pub fn init() -> PVS {
+        PVS { window: Some(1), deepening: true }
+    }
+
+    // The score and best move of the board, as AlphaBeta::search with the
+    // whole window would find them, and what it took to find them.
+    pub fn solve(&self, board: &Board) -> (isize, usize, Stats) {
+        let mut search = Search { stats: Stats::default(), best_moves: HashMap::new() };
+        let depth = board.empty().count_ones() as usize;
+
+        if !self.deepening {
+            let (score, move_) = search.run(board, depth, -MAX_SCORE, MAX_SCORE);
+            return (score, move_, search.stats);
+        }
+
+        let mut best = (0, 0);
+
+        for d in 1..depth+1 {
+            let (mut alpha, mut beta) = match self.window {
+                Some(window) if d > 1 => (best.0 - window, best.0 + window),
+                _ => (-MAX_SCORE, MAX_SCORE)
+            };
+
+            // Out of the window the score is only a bound and the move may
+            // not be the best, so it's searched again on the side it failed.
+            loop {
+                let (score, move_) = search.run(board, d, alpha, beta);
+
+                if score <= alpha {
+                    beta = alpha + 1;
+                    alpha = -MAX_SCORE;
+                } else if score >= beta {
+                    alpha = beta - 1;
+                    beta = MAX_SCORE;
+                } else {
+                    best = (score, move_);
+                    break;
+                }
+
+                search.stats.failed_windows += 1;
+            }
+
+            if best.0 != 0 {
+                break;
+            }
+        }
+
+        (best.0, best.1, search.stats)
+    }
+}
+
+impl Player for PVS {
+    fn best_move(&self, board: &Board) -> usize {
+        self.solve(board).1
+    }
+}
+
+// The nodes searched to solve every board by plain alpha-beta, alpha-beta
+// with every ordering heuristic, PVS to the end straight away and PVS with
+// iterative deepening and aspiration windows.
+pub fn compare(boards: &[Board]) -> Vec<(&'static str, usize)> {
+    let alphabeta = |heuristics| boards.iter().map(|board| {
+        let mut search = ordered::Search::init(heuristics);
+        search.run(board, -MAX_SCORE, MAX_SCORE);
+        search.nodes
+    }).sum::<usize>();
+
+    let pvs = |pvs: PVS| boards.iter().map(|board| pvs.solve(board).2.nodes).sum::<usize>();
+
+    let results = vec![
+        ("alphabeta", alphabeta(Heuristics::none())),
+        ("ordered alphabeta", alphabeta(Heuristics::all())),
+        ("pvs", pvs(PVS { window: None, deepening: false })),
+        ("pvs deepening", pvs(PVS { window: None, deepening: true })),
+        ("pvs aspiration", pvs(PVS::init()))
+    ];
+
+    for &(name, nodes) in results.iter() {
+        println!("{:>18}: {} nodes", name, nodes);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::alphabeta::AlphaBeta;
+    use crate::players::networks::supervised::ALL_BOARDS;
+
+    #[test]
+    fn test_same_scores() {
+        let players = [
+            PVS { window: None, deepening: false },
+            PVS { window: None, deepening: true },
+            PVS::init()
+        ];
+
+        for board in ALL_BOARDS.iter().step_by(5) {
+            let score = AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE).0;
+
+            for pvs in players.iter() {
+                let (pvs_score, move_, _) = pvs.solve(board);
+                assert_eq!(pvs_score, score);
+
+                let mut copy = *board;
+                copy.make(move_);
+                assert_eq!(-AlphaBeta::search(&copy, -MAX_SCORE, MAX_SCORE).0, score);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fewer_nodes() {
+        let empty = Board { tiles: [0, 0], turn: 0 };
+        let results = compare(&[empty]);
+
+        let nodes = |name| results.iter().find(|r| r.0 == name).unwrap().1;
+
+        assert!(nodes("pvs") < nodes("alphabeta"));
+        assert!(nodes("pvs aspiration") < nodes("alphabeta"));
+
+        // A win is found before the last ply.
+        let board = Board { tiles: [0b000001001, 0b000000110], turn: 0 };
+        let (score, move_, stats) = PVS::init().solve(&board);
+
+        assert_eq!((move_, board.moves_to_end(score)), (7, Some(1)));
+        assert!(stats.nodes < 20);
+    }
+}
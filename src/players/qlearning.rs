@@ -0,0 +1,253 @@
+extern crate rand;
+use rand::Rng;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::constants::*;
+use crate::game::Progress;
+use crate::board::{Board, State, FULL, PLAYER1};
+use crate::symmetries::{transform_move, inverse_symmetry};
+use crate::players::{player::Player, alphabeta::AlphaBeta};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Method {
+    // Bootstraps from the best move in the next position.
+    QLearning,
+    // Bootstraps from the move actually played, exploration included.
+    Sarsa
+}
+
+// The values of every square in a position, both seen from the side to
+// move and in the position's canonical form (see Board::canonical).
+pub struct QLearning {
+    pub table: HashMap<(u64, u64), [f64; N2]>,
+    pub method: Method,
+    pub alpha: f64,
+    pub gamma: f64,
+    pub epsilon: f64
+}
+
+impl QLearning {
+    pub fn init(method: Method) -> QLearning {
+        QLearning {
+            table: HashMap::new(),
+            method,
+            alpha: 0.3,
+            gamma: 0.9,
+            epsilon: 0.1
+        }
+    }
+
+    fn values(&self, key: (u64, u64)) -> [f64; N2] {
+        match self.table.get(&key) {
+            Some(values) => *values,
+            None => [0.0; N2]
+        }
+    }
+
+    fn best_value(&self, key: (u64, u64)) -> f64 {
+        let values = self.values(key);
+        let empty = FULL ^ key.0 ^ key.1;
+
+        let mut max_value = f64::MIN;
+
+        for (i, &value) in values.iter().enumerate() {
+            if empty & (1 << i) != 0 && value > max_value {
+                max_value = value;
+            }
+        }
+
+        max_value
+    }
+
+    // Epsilon-greedy choice, with ties broken at random. Returns the move
+    // on the board and the same move in the canonical position.
+    fn choose(&self, board: &Board, epsilon: f64) -> (usize, usize) {
+        let mut rng = rand::thread_rng();
+
+        let (key, symmetry) = board.canonical();
+        let values = self.values(key);
+
+        let moves: Vec<usize> = board.gen_moves().iter()
+            .map(|&m| transform_move(m, symmetry))
+            .collect();
+
+        let canonical_move = if rng.gen::<f64>() < epsilon {
+            moves[rng.gen_range(0, moves.len())]
+        } else {
+            let mut max_value = f64::MIN;
+            let mut best_moves: Vec<usize> = Vec::new();
+
+            for &m in moves.iter() {
+                if values[m - 1] > max_value {
+                    max_value = values[m - 1];
+                    best_moves.clear();
+                    best_moves.push(m);
+                } else if values[m - 1] == max_value {
+                    best_moves.push(m);
+                }
+            }
+
+            best_moves[rng.gen_range(0, best_moves.len())]
+        };
+
+        (transform_move(canonical_move, inverse_symmetry(symmetry)), canonical_move)
+    }
+
+    fn update(&mut self, key: (u64, u64), move_: usize, target: f64) {
+        let alpha = self.alpha;
+        let values = self.table.entry(key).or_insert([0.0; N2]);
+
+        values[move_ - 1] += alpha * (target - values[move_ - 1]);
+    }
+
+    // Plays a game and learns from every move the agent makes. Without an
+    // opponent it plays both sides, otherwise it plays as PLAYER1.
+    fn episode(&mut self, opponent: Option<&dyn Player>) {
+        let mut board = Board { tiles: [0, 0], turn: rand::thread_rng().gen_range(0, 2) };
+
+        // The last position and move of each side, still waiting for
+        // the position that follows to be updated.
+        let mut pending: [Option<((u64, u64), usize)>; 2] = [None, None];
+
+        while board.state() == State::Unfinished {
+            let turn = board.turn;
+
+            if let Some(player) = opponent {
+                if turn != PLAYER1 {
+                    board.make(player.best_move(&board));
+                    continue;
+                }
+            }
+
+            let (key, _) = board.canonical();
+            let (move_, canonical_move) = self.choose(&board, self.epsilon);
+
+            if let Some((previous_key, previous_move)) = pending[turn] {
+                let next_value = match self.method {
+                    Method::QLearning => self.best_value(key),
+                    Method::Sarsa => self.values(key)[canonical_move - 1]
+                };
+
+                self.update(previous_key, previous_move, self.gamma * next_value);
+            }
+
+            pending[turn] = Some((key, canonical_move));
+            board.make(move_);
+        }
+
+        // The final score is given from the perspective of board.turn.
+        let score = board.score() as f64;
+
+        for (turn, &last) in pending.iter().enumerate() {
+            if let Some((key, move_)) = last {
+                let reward = if turn == board.turn { score } else { -score };
+                self.update(key, move_, reward);
+            }
+        }
+    }
+
+    // Trains for a number of games, through self-play if there's no
+    // opponent, and measures the agent against AlphaBeta every so often.
+    pub fn train(&mut self, opponent: Option<&dyn Player>, games: usize, eval_every: usize) -> Vec<Progress> {
+        let ab = AlphaBeta{};
+        let mut curve = Vec::new();
+
+        for game in 1..games+1 {
+            self.episode(opponent);
+
+            if game % eval_every == 0 {
+                curve.push(Progress::measure(&*self, game, &[("alphabeta", &ab)]));
+            }
+        }
+
+        curve
+    }
+
+    // One position per line: both bitboards followed by the values.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut contents = String::new();
+
+        for (key, values) in self.table.iter() {
+            let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            contents.push_str(&format!("{} {} {}\n", key.0, key.1, values.join(" ")));
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn load(&mut self, path: &str) -> io::Result<()> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Malformed Q-table.");
+
+        self.table.clear();
+
+        for line in fs::read_to_string(path)?.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if fields.len() != N2 + 2 {
+                return Err(invalid());
+            }
+
+            let ours: u64 = fields[0].parse().map_err(|_| invalid())?;
+            let theirs: u64 = fields[1].parse().map_err(|_| invalid())?;
+
+            let mut values: [f64; N2] = [0.0; N2];
+
+            for i in 0..N2 {
+                values[i] = fields[i + 2].parse().map_err(|_| invalid())?;
+            }
+
+            self.table.insert((ours, theirs), values);
+        }
+
+        Ok(())
+    }
+}
+
+impl Player for QLearning {
+    fn best_move(&self, board: &Board) -> usize {
+        self.choose(board, 0.0).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greedy_under_symmetry() {
+        let mut agent = QLearning::init(Method::QLearning);
+        let board = Board { tiles: [0b000000011, 0b000001000], turn: 0 };
+
+        // Rewarding the winning move in the canonical position must make
+        // every image of the board play it.
+        let (key, symmetry) = board.canonical();
+        agent.update(key, transform_move(3, symmetry), 1.0);
+
+        assert_eq!(agent.best_move(&board), 3);
+
+        for s in 0..crate::symmetries::N_SYMMETRIES {
+            let image = board.transform(s);
+            assert_eq!(agent.best_move(&image), transform_move(3, s));
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut agent = QLearning::init(Method::Sarsa);
+        agent.train(None, 200, 1000);
+
+        let path = std::env::temp_dir().join("qlearning_test_table.txt");
+        let path = path.to_str().unwrap();
+
+        agent.save(path).unwrap();
+
+        let mut loaded = QLearning::init(Method::Sarsa);
+        loaded.load(path).unwrap();
+
+        assert_eq!(loaded.table, agent.table);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+extern crate rand;
+use rand::Rng;
+
+use crate::constants::*;
+use crate::game;
+use crate::board::Board;
+use crate::players::player::Player;
+
+// Plays any empty square.
+pub struct RandomPlayer;
+
+impl Player for RandomPlayer {
+    fn best_move(&self, board: &Board) -> usize {
+        board.random_move()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Noise {
+    // The chance of playing a random move instead of the player's.
+    Epsilon(f64),
+
+    // Moves are drawn from the softmax of the player's move scores over
+    // this temperature: the higher, the closer to random.
+    Softmax(f64)
+}
+
+// Weakens a player with random moves, to get opponents of any strength
+// or more varied games for training data.
+pub struct Noisy<P: Player> {
+    pub player: P,
+    pub noise: Noise
+}
+
+impl<P: Player> Noisy<P> {
+    pub fn epsilon(player: P, epsilon: f64) -> Noisy<P> {
+        Noisy { player, noise: Noise::Epsilon(epsilon) }
+    }
+
+    pub fn softmax(player: P, temperature: f64) -> Noisy<P> {
+        Noisy { player, noise: Noise::Softmax(temperature) }
+    }
+}
+
+// The chance of the player playing every square with softmax noise at
+// the temperature, which is 0 for the taken ones.
+pub fn softmax_policy<P: Player + ?Sized>(player: &P, board: &Board, temperature: f64) -> [f64; N2] {
+    assert!(temperature > 0.0);
+
+    let scores = player.move_scores(board);
+    let empty = board.empty();
+
+    // Subtracting the highest score keeps the exponentials finite.
+    let max = (0..N2).filter(|&i| empty & (1 << i) != 0)
+        .map(|i| scores[i])
+        .fold(f64::MIN, f64::max);
+
+    let mut policy: [f64; N2] = [0.0; N2];
+
+    for i in 0..N2 {
+        if empty & (1 << i) != 0 {
+            policy[i] = ((scores[i] - max) / temperature).exp();
+        }
+    }
+
+    let total: f64 = policy.iter().sum();
+
+    for p in policy.iter_mut() {
+        *p /= total;
+    }
+
+    policy
+}
+
+impl<P: Player> Player for Noisy<P> {
+    fn best_move(&self, board: &Board) -> usize {
+        let mut rng = rand::thread_rng();
+
+        match self.noise {
+            Noise::Epsilon(epsilon) => {
+                if rng.gen::<f64>() < epsilon {
+                    board.random_move()
+                } else {
+                    self.player.best_move(board)
+                }
+            },
+            Noise::Softmax(temperature) => {
+                game::sample_move(&softmax_policy(&self.player, board, temperature))
+            }
+        }
+    }
+
+    fn game_over(&self, board: &Board) {
+        self.player.game_over(board);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::play_match;
+    use crate::players::alphabeta::AlphaBeta;
+
+    #[test]
+    fn test_epsilon() {
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+
+        for _ in 0..20 {
+            let move_ = RandomPlayer.best_move(&board);
+            assert!(board.empty() & (1 << (move_ - 1)) != 0);
+
+            assert_eq!(Noisy::epsilon(AlphaBeta{}, 0.0).best_move(&board), AlphaBeta{}.best_move(&board));
+        }
+
+        // Always random, so it can't keep up with the engine.
+        let (_, losses, _) = play_match(&Noisy::epsilon(AlphaBeta{}, 1.0), &AlphaBeta{}, 20);
+        assert!(losses > 0);
+    }
+
+    #[test]
+    fn test_softmax() {
+        // 3 wins at once and 6 only blocks.
+        let board = Board { tiles: [0b000000011, 0b000011000], turn: 0 };
+
+        let cold = Noisy::softmax(AlphaBeta{}, 0.01);
+        let policy = softmax_policy(&AlphaBeta{}, &board, 0.01);
+
+        assert!(policy[2] > 0.99);
+        assert_eq!(policy[0], 0.0);
+        assert_eq!(cold.best_move(&board), 3);
+
+        // Hot enough, every empty square gets a fair share.
+        let policy = softmax_policy(&AlphaBeta{}, &board, 1000.0);
+
+        for (i, &p) in policy.iter().enumerate() {
+            assert_eq!(p > 0.1, board.empty() & (1 << i) != 0);
+        }
+    }
+}
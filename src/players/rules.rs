@@ -0,0 +1,157 @@
+use crate::constants::*;
+use crate::board::Board;
+use crate::players::player::Player;
+use crate::threats;
+
+// The rules of Newell and Simon's program, in the order they're tried.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rule {
+    Win,
+    Block,
+    Fork,
+    BlockFork,
+    Centre,
+    OppositeCorner,
+    EmptyCorner,
+    EmptySide
+}
+
+// Plays by a fixed list of priorities instead of searching, so that every
+// move can be explained by the rule that chose it.
+pub struct Rules;
+
+impl Rules {
+    // The move and the first rule that applies.
+    pub fn choose(board: &Board) -> (usize, Rule) {
+        let forced_move = board.find_forced();
+
+        if forced_move != 0 {
+            let mut copy = *board;
+            copy.make(forced_move);
+
+            let rule = if copy.score() == -1 { Rule::Win } else { Rule::Block };
+            return (forced_move, rule);
+        }
+
+        if let Some(&move_) = threats::forks(board).first() {
+            return (move_, Rule::Fork);
+        }
+
+        if let Some(move_) = Rules::block_fork(board) {
+            return (move_, Rule::BlockFork);
+        }
+
+        let empty = board.empty();
+        let is_empty = |move_: usize| empty & (1 << (move_ - 1)) != 0;
+
+        let centre = N2 / 2 + 1;
+
+        if N % 2 == 1 && is_empty(centre) {
+            return (centre, Rule::Centre);
+        }
+
+        let corners = [1, N, N2 - N + 1, N2];
+        let theirs = board.tiles[board.turn ^ 1];
+
+        for &corner in corners.iter() {
+            let opposite = N2 + 1 - corner;
+
+            if theirs & (1 << (corner - 1)) != 0 && is_empty(opposite) {
+                return (opposite, Rule::OppositeCorner);
+            }
+        }
+
+        if let Some(&corner) = corners.iter().find(|&&corner| is_empty(corner)) {
+            return (corner, Rule::EmptyCorner);
+        }
+
+        // On bigger boards the inner squares are taken as sides too.
+        (board.gen_moves()[0], Rule::EmptySide)
+    }
+
+    // A single fork of the opponent's is taken first. Against more, a
+    // threat forces them to block somewhere that doesn't fork as well.
+    fn block_fork(board: &Board) -> Option<usize> {
+        let opponent = Board { tiles: board.tiles, turn: board.turn ^ 1 };
+        let forks = threats::forks(&opponent);
+
+        match forks.len() {
+            0 => None,
+            1 => Some(forks[0]),
+            _ => {
+                let safe = threats::threat_moves(board).into_iter().find(|&move_| {
+                    let mut copy = *board;
+                    copy.make(move_);
+
+                    let block = threats::winning_squares(&copy, board.turn);
+
+                    // Two threats would be a fork, found by the rule before.
+                    copy.make(block.trailing_zeros() as usize + 1);
+                    threats::winning_squares(&copy, board.turn ^ 1).count_ones() < 2
+                });
+
+                safe.or(Some(forks[0]))
+            }
+        }
+    }
+}
+
+impl Player for Rules {
+    fn best_move(&self, board: &Board) -> usize {
+        Rules::choose(board).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::State;
+
+    #[test]
+    fn test_rules() {
+        let tests = [
+            (Board { tiles: [0b000000011, 0b000011000], turn: 0 }, (3, Rule::Win)),
+            (Board { tiles: [0b010000001, 0b000011000], turn: 0 }, (6, Rule::Block)),
+            (Board { tiles: [0b000010001, 0b100000010], turn: 0 }, (4, Rule::Fork)),
+            (Board { tiles: [0b100000001, 0b000010000], turn: 1 }, (2, Rule::BlockFork)),
+            (Board { tiles: [0b000000001, 0b000000000], turn: 1 }, (5, Rule::Centre)),
+            (Board { tiles: [0b000010000, 0b000000001], turn: 0 }, (9, Rule::OppositeCorner)),
+            (Board { tiles: [0, 0], turn: 0 }, (5, Rule::Centre)),
+            (Board { tiles: [0b000010000, 0b000000010], turn: 0 }, (1, Rule::EmptyCorner))
+        ];
+
+        for test in tests.iter() {
+            assert_eq!(Rules::choose(&test.0), test.1);
+        }
+    }
+
+    // Every game the rules can play, against every reply.
+    fn never_loses(board: &Board, player: usize) {
+        match board.state() {
+            State::Unfinished => (),
+            _ => {
+                assert!(Board { tiles: board.tiles, turn: player }.score() >= 0);
+                return;
+            }
+        }
+
+        if board.turn == player {
+            let mut copy = *board;
+            copy.make(Rules.best_move(board));
+            never_loses(&copy, player);
+        } else {
+            for move_ in board.gen_moves() {
+                let mut copy = *board;
+                copy.make(move_);
+                never_loses(&copy, player);
+            }
+        }
+    }
+
+    #[test]
+    fn test_never_loses() {
+        for &turn in [0, 1].iter() {
+            never_loses(&Board { tiles: [0, 0], turn }, 0);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+
+use crate::constants::*;
+use crate::board::{Board, State};
+use crate::players::player::Player;
+
+// Positions are indexed in base 3, a digit per square: 0 when it's empty,
+// 1 for the side to move and 2 for its opponent.
+const SIZE: usize = 19683;
+
+// Entries of positions that can't be reached or aren't canonical.
+const UNKNOWN: u8 = 0xFF;
+
+lazy_static! {
+    pub static ref TABLEBASE: Tablebase = Tablebase::generate();
+}
+
+// The value and distance to the end of the game with perfect play of every
+// reachable position, up to symmetry and colour (see Board::canonical).
+// A position takes a byte: the value plus one in the high bits and the
+// number of moves left in the low ones. The winner goes for the quickest
+// win and the loser holds out as long as it can.
+pub struct Tablebase {
+    entries: Vec<u8>
+}
+
+fn index(key: (u64, u64)) -> usize {
+    let mut index = 0;
+
+    for sqr in (0..N2).rev() {
+        index *= 3;
+
+        if key.0 & (1 << sqr) != 0 {
+            index += 1;
+        } else if key.1 & (1 << sqr) != 0 {
+            index += 2;
+        }
+    }
+
+    index
+}
+
+// The position of an index, with the side to move as player 0.
+fn key(mut index: usize) -> (u64, u64) {
+    let mut key = (0, 0);
+
+    for sqr in 0..N2 {
+        match index % 3 {
+            1 => key.0 |= 1 << sqr,
+            2 => key.1 |= 1 << sqr,
+            _ => ()
+        }
+
+        index /= 3;
+    }
+
+    key
+}
+
+fn encode(value: isize, distance: usize) -> u8 {
+    (((value + 1) as u8) << 4) | distance as u8
+}
+
+fn decode(entry: u8) -> (isize, usize) {
+    ((entry >> 4) as isize - 1, (entry & 0xF) as usize)
+}
+
+// Orders the outcomes from the side to move's point of view.
+fn rank(value: isize, distance: usize) -> isize {
+    match value {
+        1 => 2 * N2 as isize - distance as isize,
+        -1 => -2 * (N2 as isize) + distance as isize,
+        _ => 0
+    }
+}
+
+impl Tablebase {
+    // Retrograde analysis: the positions are gathered by the number of
+    // tiles on the board and solved from the fullest ones back to the
+    // empty board, every one from the positions that follow it.
+    pub fn generate() -> Tablebase {
+        let mut layers: Vec<Vec<(u64, u64)>> = vec![Vec::new(); N2 + 1];
+        let mut seen = HashSet::new();
+        let mut stack: Vec<(u64, u64)> = vec![(0, 0)];
+
+        while let Some(key) = stack.pop() {
+            if !seen.insert(key) {
+                continue;
+            }
+
+            layers[(key.0 | key.1).count_ones() as usize].push(key);
+
+            let board = Board { tiles: [key.0, key.1], turn: 0 };
+
+            if board.state() == State::Unfinished {
+                for move_ in board.gen_moves() {
+                    stack.push(Board { tiles: [key.1, key.0 | 1 << (move_ - 1)], turn: 0 }.canonical().0);
+                }
+            }
+        }
+
+        let mut tablebase = Tablebase { entries: vec![UNKNOWN; SIZE] };
+
+        for layer in layers.iter().rev() {
+            for &key in layer.iter() {
+                let board = Board { tiles: [key.0, key.1], turn: 0 };
+
+                let (value, distance) = if board.state() != State::Unfinished {
+                    (board.score(), 0)
+                } else {
+                    let (_, value, distance) = tablebase.best(&board);
+                    (value, distance)
+                };
+
+                tablebase.entries[index(key)] = encode(value, distance);
+            }
+        }
+
+        tablebase
+    }
+
+    // The value and distance to the end for the side to move, or None if
+    // the position can't be reached from the empty board.
+    pub fn probe(&self, board: &Board) -> Option<(isize, usize)> {
+        match self.entries[index(board.canonical().0)] {
+            UNKNOWN => None,
+            entry => Some(decode(entry))
+        }
+    }
+
+    // The best move with the value and distance it leads to.
+    fn best(&self, board: &Board) -> (usize, isize, usize) {
+        let mut best = (0, -2, 0);
+
+        for move_ in board.gen_moves() {
+            let mut copy = *board;
+            copy.make(move_);
+
+            let (value, distance) = self.probe(&copy).expect("The position isn't in the tablebase.");
+            let (value, distance) = (-value, distance + 1);
+
+            if best.0 == 0 || rank(value, distance) > rank(best.1, best.2) {
+                best = (move_, value, distance);
+            }
+        }
+
+        assert!(best.0 >= 1 && best.0 <= N2);
+
+        best
+    }
+
+    pub fn positions(&self) -> usize {
+        self.entries.iter().filter(|&&entry| entry != UNKNOWN).count()
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, &self.entries)
+    }
+
+    // Files that aren't a tablebase or whose entries don't follow from
+    // each other are rejected, so a bad file can't pass for perfect play.
+    pub fn load(path: &str) -> io::Result<Tablebase> {
+        let entries = fs::read(path)?;
+        let tablebase = Tablebase { entries };
+
+        if tablebase.entries.len() != SIZE || !tablebase.consistent() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Malformed tablebase."));
+        }
+
+        Ok(tablebase)
+    }
+
+    // The empty board is known, and every known position is either over
+    // with its score or has the best value and distance of its moves.
+    fn consistent(&self) -> bool {
+        let empty = Board { tiles: [0, 0], turn: 0 };
+
+        if self.probe(&empty).is_none() {
+            return false;
+        }
+
+        (0..SIZE).filter(|&i| self.entries[i] != UNKNOWN).all(|i| {
+            let (value, distance) = decode(self.entries[i]);
+            let k = key(i);
+            let board = Board { tiles: [k.0, k.1], turn: 0 };
+
+            if k.0 & k.1 != 0 || value.abs() > 1 || distance > N2 {
+                return false;
+            }
+
+            if board.state() != State::Unfinished {
+                return (value, distance) == (board.score(), 0);
+            }
+
+            let known = board.gen_moves().into_iter().all(|move_| {
+                let mut copy = board;
+                copy.make(move_);
+                self.probe(&copy).is_some()
+            });
+
+            if !known {
+                return false;
+            }
+
+            let (_, best_value, best_distance) = self.best(&board);
+            (best_value, best_distance) == (value, distance)
+        })
+    }
+}
+
+impl Player for Tablebase {
+    fn best_move(&self, board: &Board) -> usize {
+        self.best(board).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::alphabeta::AlphaBeta;
+    use crate::players::networks::supervised::ALL_BOARDS;
+
+    #[test]
+    fn test_agrees_with_alphabeta() {
+        for board in ALL_BOARDS.iter() {
+            let (value, distance) = TABLEBASE.probe(board).unwrap();
+            let score = AlphaBeta::search(board, -MAX_SCORE, MAX_SCORE).0;
+            assert_eq!(value, score.signum());
+
+            if value != 0 {
+                assert_eq!(board.moves_to_end(score), Some(distance));
+            }
+
+            let mut copy = *board;
+            copy.make(TABLEBASE.best_move(board));
+            assert_eq!(-TABLEBASE.probe(&copy).unwrap().0, value);
+        }
+
+        let empty = Board { tiles: [0, 0], turn: 0 };
+        assert_eq!(TABLEBASE.probe(&empty), Some((0, N2)));
+    }
+
+    #[test]
+    fn test_distances() {
+        // 3 and 9 win at once, 7 only after the opponent has blocked one.
+        let board = Board { tiles: [0b000010011, 0b010101000], turn: 0 };
+        assert_eq!(TABLEBASE.probe(&board), Some((1, 1)));
+        assert!(TABLEBASE.best_move(&board) == 3 || TABLEBASE.best_move(&board) == 9);
+
+        let mut slow = board;
+        slow.make(7);
+        assert_eq!(TABLEBASE.probe(&slow), Some((-1, 2)));
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join("tablebase_test.bin");
+        let path = path.to_str().unwrap();
+
+        TABLEBASE.save(path).unwrap();
+        let loaded = Tablebase::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.entries, TABLEBASE.entries);
+        assert!(loaded.positions() > 0 && loaded.positions() < 1000);
+    }
+
+    #[test]
+    fn test_load_rejects_bad_files() {
+        let path = std::env::temp_dir().join("tablebase_bad_test.bin");
+        let path = path.to_str().unwrap();
+
+        // The right size, but every position lost at once.
+        std::fs::write(path, vec![0; SIZE]).unwrap();
+        assert!(Tablebase::load(path).is_err());
+
+        // One position off by a move.
+        let mut entries = TABLEBASE.entries.clone();
+        let i = entries.iter().position(|&entry| entry != UNKNOWN && decode(entry).1 > 0).unwrap();
+        entries[i] += 1;
+
+        std::fs::write(path, &entries).unwrap();
+        assert!(Tablebase::load(path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
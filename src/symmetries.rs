@@ -0,0 +1,185 @@
+use crate::constants::*;
+use crate::board::Board;
+
+// The eight symmetries of the square: the identity, three rotations
+// and four reflections.
+pub const N_SYMMETRIES: usize = 8;
+
+// Maps a square, indexed from 0, to where it lands under the symmetry.
+pub fn transform_square(sqr: usize, symmetry: usize) -> usize {
+    let (row, col) = (sqr / N, sqr % N);
+    let last = N - 1;
+
+    let (row, col) = match symmetry {
+        0 => (row, col),
+        1 => (col, last - row),
+        2 => (last - row, last - col),
+        3 => (last - col, row),
+        4 => (row, last - col),
+        5 => (last - row, col),
+        6 => (col, row),
+        7 => (last - col, last - row),
+        _ => panic!("There are only {} symmetries.", N_SYMMETRIES)
+    };
+
+    row * N + col
+}
+
+// The symmetry that undoes the given one.
+pub fn inverse_symmetry(symmetry: usize) -> usize {
+    match symmetry {
+        1 => 3,
+        3 => 1,
+        _ => symmetry
+    }
+}
+
+pub fn transform_bitboard(bb: u64, symmetry: usize) -> u64 {
+    let mut transformed: u64 = 0;
+    let mut bb = bb;
+
+    while bb != 0 {
+        let sqr = bb.trailing_zeros() as usize;
+        transformed |= 1 << transform_square(sqr, symmetry);
+        bb &= bb - 1;
+    }
+
+    transformed
+}
+
+// Moves are indexed from 1, like everywhere else.
+pub fn transform_move(move_: usize, symmetry: usize) -> usize {
+    transform_square(move_ - 1, symmetry) + 1
+}
+
+// Moves the score of every square to where the square lands. Anything
+// after the squares, like the value of the position, is kept as it is.
+pub fn transform_scores(scores: &[f64], symmetry: usize) -> Vec<f64> {
+    assert!(scores.len() >= N2);
+
+    let mut transformed = scores.to_vec();
+
+    for sqr in 0..N2 {
+        transformed[transform_square(sqr, symmetry)] = scores[sqr];
+    }
+
+    transformed
+}
+
+// The distinct images of a board, each with its target moved along.
+pub fn augment(board: &Board, target: &[f64]) -> Vec<(Board, Vec<f64>)> {
+    let mut images: Vec<(Board, Vec<f64>)> = Vec::new();
+
+    for symmetry in 0..N_SYMMETRIES {
+        let image = board.transform(symmetry);
+
+        if images.iter().all(|(b, _)| *b != image) {
+            images.push((image, transform_scores(target, symmetry)));
+        }
+    }
+
+    images
+}
+
+impl Board {
+    pub fn transform(&self, symmetry: usize) -> Board {
+        Board {
+            tiles: [
+                transform_bitboard(self.tiles[0], symmetry),
+                transform_bitboard(self.tiles[1], symmetry)
+            ],
+            turn: self.turn
+        }
+    }
+
+    // Identifies a position up to symmetry and colour: the side to move's
+    // tiles and its opponent's, taking the smallest of the eight images.
+    // Also returns the symmetry that takes the board to its canonical form.
+    pub fn canonical(&self) -> ((u64, u64), usize) {
+        let ours = self.tiles[self.turn];
+        let theirs = self.tiles[self.turn ^ 1];
+
+        let mut best_key = (ours, theirs);
+        let mut best_symmetry = 0;
+
+        for symmetry in 1..N_SYMMETRIES {
+            let key = (transform_bitboard(ours, symmetry), transform_bitboard(theirs, symmetry));
+
+            if key < best_key {
+                best_key = key;
+                best_symmetry = symmetry;
+            }
+        }
+
+        (best_key, best_symmetry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::State;
+
+    #[test]
+    fn test_inverse_symmetry() {
+        for symmetry in 0..N_SYMMETRIES {
+            for sqr in 0..N2 {
+                let transformed = transform_square(sqr, symmetry);
+                assert_eq!(transform_square(transformed, inverse_symmetry(symmetry)), sqr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distinct_symmetries() {
+        // A board without symmetries has eight different images.
+        let board = Board { tiles: [0b000000011, 0b000001000], turn: 0 };
+        let mut images: Vec<Board> = Vec::new();
+
+        for symmetry in 0..N_SYMMETRIES {
+            let image = board.transform(symmetry);
+
+            assert!(!images.contains(&image));
+            assert!(image.state() == board.state());
+            images.push(image);
+        }
+    }
+
+    #[test]
+    fn test_augment() {
+        // Winning at 3, on a board without symmetries.
+        let board = Board { tiles: [0b000000011, 0b000011000], turn: 0 };
+        let mut target = vec![0.0; N2 + 1];
+        target[2] = 1.0;
+        target[N2] = 1.0;
+
+        let images = augment(&board, &target);
+        assert_eq!(images.len(), N_SYMMETRIES);
+
+        for (image, target) in images.iter() {
+            let move_ = target.iter().position(|&x| x == 1.0).unwrap() + 1;
+            let mut copy = *image;
+            copy.make(move_);
+
+            assert!(copy.state() != State::Unfinished);
+            assert_eq!(target[N2], 1.0);
+        }
+
+        // The centre alone is the same under every symmetry.
+        let centre = Board { tiles: [0b000010000, 0], turn: 1 };
+        assert_eq!(augment(&centre, &[0.0; N2]).len(), 1);
+    }
+
+    #[test]
+    fn test_canonical() {
+        let board = Board { tiles: [0b000000011, 0b000001000], turn: 0 };
+        let (key, _) = board.canonical();
+
+        for symmetry in 0..N_SYMMETRIES {
+            let image = board.transform(symmetry);
+
+            assert_eq!(image.canonical().0, key);
+            assert_eq!(image.inverse().canonical().0, key);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::constants::*;
+use crate::bitboards::WINNING_STATES;
+use crate::board::{Board, State};
+
+// The empty squares of every line the player could still complete with
+// `missing` more tiles, with none of the opponent's in the way. A line
+// missing one is a threat to win on the next move. On bigger boards the
+// lines missing two and three are the open threes and fours.
+pub fn lines(board: &Board, player: usize, missing: usize) -> Vec<u64> {
+    let empty = board.empty();
+
+    WINNING_STATES.iter()
+        .filter(|&&comb| comb & board.tiles[player ^ 1] == 0)
+        .map(|&comb| comb & empty)
+        .filter(|&squares| squares.count_ones() as usize == missing)
+        .collect()
+}
+
+// The squares where the player would win at once.
+pub fn winning_squares(board: &Board, player: usize) -> u64 {
+    lines(board, player, 1).iter().fold(0, |squares, &line| squares | line)
+}
+
+// The moves that leave the side to move a threat.
+pub fn threat_moves(board: &Board) -> Vec<usize> {
+    let squares = lines(board, board.turn, 2).iter().fold(0, |squares, &line| squares | line);
+    to_moves(squares)
+}
+
+// The moves that leave the side to move two threats on different squares,
+// which can't both be blocked.
+pub fn forks(board: &Board) -> Vec<usize> {
+    threat_moves(board).into_iter().filter(|&move_| {
+        let mut copy = *board;
+        copy.make(move_);
+
+        winning_squares(&copy, board.turn).count_ones() >= 2
+    }).collect()
+}
+
+fn to_moves(mut squares: u64) -> Vec<usize> {
+    let mut moves = Vec::new();
+
+    while squares != 0 {
+        moves.push(squares.trailing_zeros() as usize + 1);
+        squares &= squares - 1;
+    }
+
+    moves
+}
+
+// Threat-space search: a win for the side to move in which every move of
+// its own wins or leaves a threat, so that every reply is forced. Moves
+// that block a threat of the opponent's count only if they make one as
+// well. Returns the moves of both sides up to the winning one, with at
+// most `depth` moves of the winner, or None if there's no such win.
+pub fn search(board: &Board, depth: usize) -> Option<Vec<usize>> {
+    if board.state() != State::Unfinished {
+        return None;
+    }
+
+    let attacker = board.turn;
+    let ours = winning_squares(board, attacker);
+
+    if ours != 0 {
+        return Some(vec![ours.trailing_zeros() as usize + 1]);
+    }
+
+    if depth <= 1 {
+        return None;
+    }
+
+    let theirs = winning_squares(board, attacker ^ 1);
+
+    let moves = match theirs.count_ones() {
+        0 => threat_moves(board),
+        1 => vec![theirs.trailing_zeros() as usize + 1],
+        _ => return None
+    };
+
+    for move_ in moves {
+        let mut copy = *board;
+        copy.make(move_);
+
+        let threats = winning_squares(&copy, attacker);
+
+        if threats == 0 || copy.state() != State::Unfinished || winning_squares(&copy, attacker ^ 1) != 0 {
+            continue;
+        }
+
+        // Blocking one threat of a fork still loses, so any block will do.
+        let reply = threats.trailing_zeros() as usize + 1;
+        copy.make(reply);
+
+        if let Some(line) = search(&copy, depth - 1) {
+            let mut sequence = vec![move_, reply];
+            sequence.extend(line);
+
+            return Some(sequence);
+        }
+    }
+
+    None
+}
+
+// Prints the board with the threats of both sides, the forks of the side
+// to move and the win found by threat-space search, if any.
+pub fn analyse(board: &Board) {
+    board.print();
+
+    for &player in [board.turn, board.turn ^ 1].iter() {
+        let who = if player == board.turn { "Side to move" } else { "Opponent" };
+
+        println!("{}: wins on {:?}, lines missing two on {:?}", who,
+            to_moves(winning_squares(board, player)),
+            lines(board, player, 2).into_iter().map(to_moves).collect::<Vec<Vec<usize>>>());
+    }
+
+    println!("Forks: {:?}", forks(board));
+
+    match search(board, N2) {
+        Some(line) => println!("Forced win: {:?}", line),
+        None => println!("No forced win by threats.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::oracle::Oracle;
+    use crate::players::networks::supervised::ALL_BOARDS;
+
+    #[test]
+    fn test_threats() {
+        // X on 1 and 5, O on 9 and 2: X threatens nothing yet.
+        let board = Board { tiles: [0b000010001, 0b100000010], turn: 0 };
+
+        assert_eq!(winning_squares(&board, 0), 0);
+        assert_eq!(winning_squares(&board, 1), 0);
+        assert_eq!(threat_moves(&board), vec![3, 4, 6, 7]);
+
+        // 4 threatens 6 and 7, and 7 threatens 3 and 4. After 3 the
+        // block on 7 threatens 8, which X can't take with a threat.
+        assert_eq!(forks(&board), vec![4, 7]);
+        assert_eq!(search(&board, N2), Some(vec![4, 6, 7]));
+    }
+
+    #[test]
+    fn test_forced_wins() {
+        let mut found = 0;
+
+        for board in ALL_BOARDS.iter().filter(|b| b.state() == State::Unfinished) {
+            if let Some(line) = search(board, N2) {
+                found += 1;
+                assert_eq!(Oracle::value(board), 1);
+
+                // The line is legal and ends with the attacker's win.
+                let mut copy = *board;
+
+                for &move_ in line.iter() {
+                    copy.make(move_);
+                }
+
+                assert_eq!(copy.turn, board.turn ^ 1);
+                assert_eq!(copy.score(), -1);
+            }
+        }
+
+        assert!(found > 0);
+    }
+}